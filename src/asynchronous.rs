@@ -0,0 +1,273 @@
+//! Async versions of the main functions, using `reqwest`
+//!
+//! Inputs and caches are still read and written through the (blocking) `Storage`, as they are small files.
+//! Unlike the blocking functions, submissions don't lock the cache file while waiting for the server
+
+use crate::{
+	backend::{
+		base_url, classify_submission, is_html_page, is_logged_out_page, locked_input_error,
+		proxy_url, reserve_request_slot, user_agent,
+	},
+	calendar,
+	error::{Error, NotSubmittedReason, Result},
+	log_run, practice_mode, read_stored, read_stored_string, run_solution,
+	session::cookie_header,
+	submission_blocked, validate_cached_input, write_atomic, InputSpec, RunTiming,
+};
+use std::{
+	fmt::Display,
+	path::Path,
+	sync::{PoisonError, RwLock},
+	time::{Duration, Instant},
+};
+
+fn map_reqwest_error(error: reqwest::Error) -> Error { Error::Reqwest(Some(Box::new(error))) }
+
+/// Client shared by all requests, along with the proxy it was built for
+static CLIENT: RwLock<Option<(Option<String>, reqwest::Client)>> = RwLock::new(None);
+
+/// Client using the proxy from `set_proxy` or the environment, shared so connections are pooled
+fn client() -> Result<reqwest::Client> {
+	if practice_mode() {
+		return Err(Error::PracticeMode);
+	}
+	let proxy = proxy_url();
+	if let Some((built_for, client)) = &*CLIENT.read().unwrap_or_else(PoisonError::into_inner) {
+		if *built_for == proxy {
+			return Ok(client.clone());
+		}
+	}
+
+	let builder = reqwest::Client::builder();
+	let builder = match &proxy {
+		Some(url) => builder.proxy(
+			reqwest::Proxy::all(url).map_err(|e| Error::InvalidProxy(format!("{url:?}: {e}")))?,
+		),
+		None => builder,
+	};
+	let client = builder.build().map_err(map_reqwest_error)?;
+	*CLIENT.write().unwrap_or_else(PoisonError::into_inner) = Some((proxy, client.clone()));
+	Ok(client)
+}
+
+async fn request(
+	session: &str,
+	request: reqwest::RequestBuilder,
+) -> Result<(reqwest::StatusCode, Option<reqwest::Error>, String)> {
+	tokio::time::sleep(reserve_request_slot()).await;
+	let resp = request
+		.header("User-Agent", user_agent())
+		.header("Cookie", cookie_header(session).as_str())
+		.send()
+		.await
+		.map_err(map_reqwest_error)?;
+	if let Some(date) = resp
+		.headers()
+		.get(reqwest::header::DATE)
+		.and_then(|date| date.to_str().ok())
+	{
+		crate::clock::record_server_date(date);
+	}
+	let status = resp.status();
+	let status_error = resp.error_for_status_ref().err();
+	let body = resp.text().await.map_err(map_reqwest_error)?;
+	if status == reqwest::StatusCode::BAD_REQUEST || is_logged_out_page(&body) {
+		return Err(Error::SessionInvalid);
+	}
+	Ok((status, status_error, body))
+}
+
+/// Async version of `get_input`
+pub async fn get_input_async(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+) -> Result<String> {
+	let (year, day) = (year.into(), day.into());
+	calendar::check_puzzle(year, day)?;
+	let url = format!("{}/{}/day/{}/input", base_url(), year, day);
+	let (status, status_error, mut body) = request(session, client()?.get(url)).await?;
+	if let Some(e) = status_error {
+		if status == reqwest::StatusCode::NOT_FOUND {
+			if let Some(locked) = locked_input_error(year, day, &body) {
+				return Err(locked);
+			}
+		}
+		return Err(map_reqwest_error(e));
+	}
+	if is_html_page(&body) {
+		return Err(locked_input_error(year, day, &body).unwrap_or_else(|| {
+			Error::InvalidResponse("an HTML page was returned instead of the input".to_owned())
+		}));
+	}
+
+	// Remove trailing newline if one exists
+	if body.ends_with('\n') {
+		body.pop();
+	}
+
+	Ok(body)
+}
+
+async fn input_or_file_async(session: &str, year: i32, day: i32, path: &Path) -> Result<String> {
+	match read_stored(path).map(String::from_utf8) {
+		Ok(Ok(input)) if validate_cached_input(&input) => Ok(input),
+		_ => {
+			let input = get_input_async(session, year, day).await?;
+			write_atomic(path, input.as_bytes())?;
+			Ok(input)
+		}
+	}
+}
+
+/// Async version of `post_answer`
+pub async fn post_answer_async<SolOutput>(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	cache_path: Option<impl AsRef<Path>>,
+	answer: SolOutput,
+) -> Result<()>
+where
+	SolOutput: Display,
+{
+	let answer = answer.to_string();
+	let cache_path = cache_path.as_ref().map(AsRef::as_ref);
+	post_answer_timed(session, year, day, part, cache_path, &answer, false)
+		.await
+		.0
+		.map_err(|e| e.in_challenge(year, day, part))
+}
+
+/// `post_answer_async`, also returning how long the submission took if one was made
+///
+/// Answers solved from a non-canonical input (see `InputSpec::is_canonical`) are only checked against the cache,
+/// as there is no way to confirm them
+async fn post_answer_timed(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	#[cfg_attr(not(feature = "local_cache"), allow(unused))] cache_path: Option<&Path>,
+	answer: &str,
+	non_canonical_input: bool,
+) -> (Result<()>, Option<Duration>) {
+	if let Err(e) = calendar::check_puzzle(year, day).and_then(|()| calendar::check_part(part)) {
+		return (Err(e), None);
+	}
+	let post = || async {
+		let blocked = submission_blocked()
+			.or(non_canonical_input.then_some(NotSubmittedReason::NonCanonicalInput));
+		if let Some(reason) = blocked {
+			return (Err(Error::NotSubmitted(reason)), None);
+		}
+		let start = Instant::now();
+		let url = format!("{}/{}/day/{}/answer", base_url(), year, day);
+		let form = [("level", part.to_string()), ("answer", answer.to_owned())];
+		let client = match client() {
+			Ok(client) => client,
+			Err(e) => return (Err(e), None),
+		};
+		let outcome = match request(session, client.post(url).form(&form)).await {
+			Ok((_, Some(e), _)) => Err(map_reqwest_error(e)),
+			Ok((_, None, body)) => classify_submission(&body),
+			Err(e) => Err(e),
+		};
+		(outcome, Some(start.elapsed()))
+	};
+
+	#[cfg(feature = "local_cache")]
+	{
+		use crate::cache::{PendingSubmission, Revalidation};
+
+		let revalidation = Revalidation::from_env(None);
+		match PendingSubmission::begin(cache_path, None, part, answer, None, revalidation) {
+			Err(cached) => (cached.map(|_| ()), None),
+			Ok(pending) => {
+				let (outcome, submit_time) = post().await;
+				(pending.finish(outcome).map(|_| ()), submit_time)
+			}
+		}
+	}
+
+	#[cfg(not(feature = "local_cache"))]
+	{
+		let (outcome, submit_time) = post().await;
+		(outcome.map(|_| ()), submit_time)
+	}
+}
+
+/// Async version of `calculate_and_post`
+///
+/// The solution itself is run synchronously
+pub async fn calculate_and_post_async<SolOutput, SolFn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	part: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	let year = year.into();
+	let day = day.into();
+	let part = part.into();
+	solve_and_post_async(session, year, day, part, input.into(), cache_path, solution)
+		.await
+		.map_err(|e| e.in_challenge(year, day, part))
+}
+
+async fn solve_and_post_async<SolOutput, SolFn>(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	input: InputSpec,
+	cache_path: Option<impl AsRef<Path>>,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	calendar::check_unlocked(year, day)?;
+
+	let non_canonical_input = !input.is_canonical();
+	let input = match input {
+		InputSpec::Default => get_input_async(session, year, day).await?,
+		InputSpec::File {
+			path,
+			never_download: false,
+		} => input_or_file_async(session, year, day, &path).await?,
+		InputSpec::File {
+			path,
+			never_download: true,
+		} => read_stored_string(&path)?,
+		InputSpec::Literal(input) => input,
+	};
+	let (answer, solve_time) = run_solution(solution, &input)?;
+	let cache_path = cache_path.as_ref().map(AsRef::as_ref);
+	let (result, submit_time) = post_answer_timed(
+		session,
+		year,
+		day,
+		part,
+		cache_path,
+		&answer,
+		non_canonical_input,
+	)
+	.await;
+	let timing = RunTiming {
+		solve_time,
+		submit_time,
+		non_canonical_input,
+		..RunTiming::solved(solve_time)
+	};
+	log_run(year, day, part, &answer, timing, &result);
+	result
+}
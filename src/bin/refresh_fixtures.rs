@@ -0,0 +1,92 @@
+//! Maintainer tool which records real submission responses as test fixtures
+//!
+//! Usage: `AOC_SESSION=<throwaway session> cargo run --features maintainer --bin refresh_fixtures -- <year> <day> <unsolved part> <solved part>`
+//!
+//! Deliberately submits a wrong answer, then a second wrong answer straight away (to be rate-limited),
+//! then an answer for a part that is already solved. Each response is reduced to its `<main>` element
+//! (dropping the account specific page header), stored under `tests/fixtures/`,
+//! and checked against `backend::classify_submission`. `tests/fixtures.rs` checks the stored fixtures on every test run,
+//! so commit the refreshed files.
+
+use aoc_driver::{
+	backend::{classify_submission, SubmissionOutcome, UreqBackend},
+	error::{Error, Result},
+};
+use std::{fs, path::Path, process::ExitCode};
+
+const FIXTURE_DIR: &str = "tests/fixtures";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Class {
+	Incorrect,
+	RateLimit,
+	AlreadyComplete,
+	Correct,
+	Other,
+}
+
+impl Class {
+	fn of(result: &Result<SubmissionOutcome>) -> Self {
+		match result {
+			Ok(SubmissionOutcome::Correct) => Class::Correct,
+			Ok(SubmissionOutcome::AlreadyComplete) => Class::AlreadyComplete,
+			Err(e) if e.is_incorrect() => Class::Incorrect,
+			Err(Error::RateLimit(_)) => Class::RateLimit,
+			Err(_) => Class::Other,
+		}
+	}
+}
+
+fn sanitize(body: &str) -> &str {
+	let start = body.find("<main>").unwrap_or(0);
+	let end = body
+		.find("</main>")
+		.map_or(body.len(), |i| i + "</main>".len());
+	&body[start..end]
+}
+
+fn main() -> ExitCode {
+	let session = std::env::var("AOC_SESSION").expect("AOC_SESSION must be set");
+	let args = std::env::args()
+		.skip(1)
+		.map(|a| a.parse::<i32>().expect("arguments must be integers"))
+		.collect::<Vec<_>>();
+	let [year, day, unsolved_part, solved_part] = args[..]
+	else {
+		eprintln!("usage: refresh_fixtures <year> <day> <unsolved part> <solved part>");
+		return ExitCode::FAILURE;
+	};
+
+	let backend = UreqBackend::new(&session);
+	let wrong_answer = "aoc_driver fixture";
+	let cases = [
+		("incorrect", unsolved_part, Class::Incorrect),
+		("rate_limit", unsolved_part, Class::RateLimit),
+		("already_complete", solved_part, Class::AlreadyComplete),
+	];
+
+	fs::create_dir_all(FIXTURE_DIR).expect("could not create fixture directory");
+	let mut ok = true;
+	for (name, part, expected) in cases {
+		let body = backend
+			.submit_raw(year, day, part, wrong_answer)
+			.expect("submission failed");
+		let body = sanitize(&body);
+		let path = Path::new(FIXTURE_DIR).join(format!("{name}.html"));
+		fs::write(&path, body).expect("could not write fixture");
+
+		let class = Class::of(&classify_submission(body));
+		if class == expected {
+			println!("{name}: ok ({})", path.display());
+		}
+		else {
+			println!("{name}: expected {expected:?} but classified as {class:?}");
+			ok = false;
+		}
+	}
+
+	match ok {
+		true => ExitCode::SUCCESS,
+		false => ExitCode::FAILURE,
+	}
+}
@@ -0,0 +1,142 @@
+//! Searching for an integer answer using the server's "too high" / "too low" hints
+//!
+//! Every submission counts against the answer cooldown and shows up on the server as a wrong answer,
+//! so `bisect_submit` asks before each one and gives up early rather than guessing
+
+use crate::{
+	backend::{TimedBackend, UreqBackend},
+	cache::read_day_cache_or_default,
+	error::{Hint, NotSubmittedReason, Result},
+	log_run, submit_cached, RunTiming, SubmitOptions, SubmitOutcome,
+};
+use std::{path::Path, time::Duration};
+
+/// Limits on `bisect_submit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BisectOptions {
+	/// Most answers sent to the server
+	pub max_attempts: u32,
+	/// Longest total time to wait out rate limits for
+	pub max_wait: Duration,
+}
+
+impl Default for BisectOptions {
+	/// 5 attempts, waiting up to 10 minutes in total
+	fn default() -> Self {
+		Self {
+			max_attempts: 5,
+			max_wait: Duration::from_secs(10 * 60),
+		}
+	}
+}
+
+/// Why `bisect_submit` stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisectEnd {
+	/// This answer is correct
+	Correct(i64),
+	/// The part was already complete, but the cache doesn't know the answer
+	AlreadyComplete,
+	/// The hints leave no possible answer between `low` and `high`
+	Inconsistent { low: i64, high: i64 },
+	/// The server said an answer was wrong without saying which way
+	NoHint(i64),
+	/// Submitting this answer wasn't confirmed
+	Declined(i64),
+	/// `max_attempts` answers were submitted without finding the right one
+	AttemptLimit,
+	/// A rate limit lasted longer than `max_wait`
+	RateLimited(Duration),
+	/// The answer couldn't be submitted, e.g. in CI
+	NotSubmitted(NotSubmittedReason),
+}
+
+/// What `bisect_submit` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BisectReport {
+	/// Each answer that was submitted, with the outcome, in order
+	pub attempts: Vec<(i64, SubmitOutcome)>,
+	pub end: BisectEnd,
+}
+
+/// Binary searches for an integer answer between `low` and `high` (inclusive) by submitting guesses
+///
+/// The search starts from the bounds in the cache at `cache_path`, and every guess is recorded there and in the run log.
+/// `confirm` is called with each guess before it is submitted, and the search stops if it returns `false`.
+/// Rate limits are waited out, for up to `options.max_wait` in total
+///
+/// Returns `Err` if a guess couldn't be submitted, e.g. because of a connection problem
+#[allow(clippy::too_many_arguments)]
+pub fn bisect_submit(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	low: i64,
+	high: i64,
+	cache_path: impl AsRef<Path>,
+	options: BisectOptions,
+	mut confirm: impl FnMut(i64) -> bool,
+) -> Result<BisectReport> {
+	let cache_path = cache_path.as_ref();
+	let backend = UreqBackend::new(session);
+	let submit_options = SubmitOptions {
+		label: Some("bisect"),
+		rate_limit_wait: Some(options.max_wait),
+		..SubmitOptions::cached_at(Some(cache_path))
+	};
+
+	let (mut low, mut high) = (low, high);
+	let mut attempts = Vec::new();
+	let end = loop {
+		let cache = read_day_cache_or_default(cache_path);
+		if let Some(answer) = cache
+			.correct_answer(part)
+			.and_then(|answer| answer.parse().ok())
+		{
+			break BisectEnd::Correct(answer);
+		}
+		let (cached_low, cached_high) = cache.bounds(part);
+		low = cached_low.map_or(low, |cached| cached.max(low));
+		high = cached_high.map_or(high, |cached| cached.min(high));
+		if low > high {
+			break BisectEnd::Inconsistent { low, high };
+		}
+		if attempts.len() as u32 >= options.max_attempts {
+			break BisectEnd::AttemptLimit;
+		}
+
+		let guess = ((i128::from(low) + i128::from(high)) / 2) as i64;
+		if !confirm(guess) {
+			break BisectEnd::Declined(guess);
+		}
+		let timed = TimedBackend::new(&backend);
+		let result = submit_cached(&timed, year, day, part, submit_options, guess);
+		let timing = RunTiming {
+			submit_time: timed.submit_time(),
+			..RunTiming::solved(Duration::ZERO)
+		};
+		let outcome = SubmitOutcome::from_result(result)?;
+		log_run(
+			year,
+			day,
+			part,
+			&guess.to_string(),
+			timing,
+			&outcome.into_result(),
+		);
+		attempts.push((guess, outcome));
+
+		match outcome {
+			SubmitOutcome::Correct => break BisectEnd::Correct(guess),
+			SubmitOutcome::AlreadyComplete => break BisectEnd::AlreadyComplete,
+			SubmitOutcome::Incorrect(Hint::TooHigh) => high = guess.saturating_sub(1),
+			SubmitOutcome::Incorrect(Hint::TooLow) => low = guess.saturating_add(1),
+			SubmitOutcome::Incorrect(Hint::Unknown) => break BisectEnd::NoHint(guess),
+			SubmitOutcome::RateLimited(wait) => break BisectEnd::RateLimited(wait),
+			SubmitOutcome::NotSubmitted(reason) => break BisectEnd::NotSubmitted(reason),
+		}
+	};
+
+	Ok(BisectReport { attempts, end })
+}
@@ -1,149 +1,818 @@
+//! Local cache of submitted answers, stored as one JSON file per day
+
 use crate::{
-	error::{Error, ErrorSerializable},
-	Result,
+	backend::SubmissionOutcome,
+	duration,
+	error::{Error, ErrorSerializable, Hint, RateLimit},
+	read_stored,
+	storage::{storage, LockGuard},
+	write_atomic, Result,
 };
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::{
-	collections::{hash_map::Entry, HashMap},
-	path::Path,
+	cmp::Reverse,
+	collections::HashMap,
+	path::{Path, PathBuf},
+	vec::IntoIter,
 };
 
+/// Version of the on-disk cache format described by `json_schema`
+///
+/// Bumped whenever the layout of the cache files changes, along with a new example cache in `tests/fixtures/cache`
+pub const FORMAT_VERSION: u32 = 5;
+
+/// Environment variable which makes `calculate_and_post` and `post_answer` submit an answer to the server
+/// even though the cache already knows a different correct answer
+///
+/// If the server accepts it, the cached correct answer is replaced and the old one kept in `superseded_answers`
+pub const FORCE_SERVER_CHECK_VAR: &str = "AOC_FORCE_SERVER_CHECK";
+
+/// When `cache_wrapper` asks the server about an answer the cache already has a response for
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Revalidation {
+	/// Submit answers which differ from the known correct answer - see `FORCE_SERVER_CHECK_VAR`
+	pub(crate) force_server_check: bool,
+	/// Age after which incorrect responses are ignored and the answer submitted again. Correct ones never expire
+	pub(crate) cache_ttl: Option<std::time::Duration>,
+}
+
+impl Revalidation {
+	/// Forces server checks if `FORCE_SERVER_CHECK_VAR` is set
+	pub(crate) fn from_env(cache_ttl: Option<std::time::Duration>) -> Self {
+		Self {
+			force_server_check: std::env::var_os(FORCE_SERVER_CHECK_VAR).is_some(),
+			cache_ttl,
+		}
+	}
+}
+
+/// Environment variable which limits how many entries (besides the correct one) are kept per part whenever a cache is written
+///
+/// Older entries are removed as by `PrunePolicy::KeepCorrectAndLastN`
+pub const MAX_ENTRIES_VAR: &str = "AOC_CACHE_MAX_ENTRIES";
+
+/// JSON Schema (draft 2020-12) describing the cache files written by this version of the crate
+pub fn json_schema() -> String {
+	let schema = serde_json::json!({
+		"$schema": "https://json-schema.org/draft/2020-12/schema",
+		"title": format!("aoc_driver cache (format version {FORMAT_VERSION})"),
+		"type": "object",
+		"required": ["parts"],
+		"properties": {
+			"parts": {
+				"description": "Submissions keyed by part number",
+				"type": "object",
+				"propertyNames": { "pattern": "^-?[0-9]+$" },
+				"additionalProperties": { "$ref": "#/$defs/part" }
+			}
+		},
+		"$defs": {
+			"part": {
+				"description": "Known correct answer plus every submitted answer keyed by the answer itself",
+				"type": "object",
+				"properties": {
+					"correct_answer": { "type": "string" },
+					"superseded_answers": {
+						"description": "Previous correct answers which the server no longer accepts (added in format version 3)",
+						"type": "array",
+						"items": { "type": "string" }
+					},
+					"lower_bound": {
+						"description": "Smallest integer answer not yet ruled out by a \"too low\" response (added in format version 5)",
+						"type": "integer"
+					},
+					"upper_bound": {
+						"description": "Largest integer answer not yet ruled out by a \"too high\" response (added in format version 5)",
+						"type": "integer"
+					}
+				},
+				"additionalProperties": { "$ref": "#/$defs/response" }
+			},
+			"response": {
+				"type": "object",
+				"required": ["submission_time", "response"],
+				"properties": {
+					"submission_time": { "type": "string", "format": "date-time" },
+					"label": {
+						"description": "Which solution produced the answer (added in format version 2)",
+						"type": "string"
+					},
+					"response": {
+						"oneOf": [
+							{
+								"type": "object",
+								"required": ["Ok"],
+								"properties": { "Ok": { "type": "null" } },
+								"additionalProperties": false
+							},
+							{
+								"type": "object",
+								"required": ["Err"],
+								"properties": { "Err": { "$ref": "#/$defs/error" } },
+								"additionalProperties": false
+							}
+						]
+					}
+				}
+			},
+			"error": {
+				"oneOf": [
+					{ "enum": ["IO", "UReq", "Reqwest", "Timeout", "NotSubmitted", "Incorrect", "Panic", "NonIntegerAnswer", "NonDeterministicAnswer", "InvalidArgument", "ExampleFailed", "PuzzleNotYetAvailable", "InvalidCache", "InvalidDuration", "InvalidUserAgent", "InvalidProxy", "InvalidResponse", "PracticeMode", "SessionInvalid", "SessionMissing", "PuzzleLocked", "NoCurrentPuzzle", "CacheConflict"] },
+					{
+						"type": "object",
+						"required": ["RateLimit"],
+						"properties": { "RateLimit": { "type": "string" } },
+						"additionalProperties": false
+					},
+					{
+						"description": "Incorrect answer the server said was too high or too low (added in format version 4)",
+						"type": "object",
+						"required": ["IncorrectWithHint"],
+						"properties": { "IncorrectWithHint": { "enum": ["TooHigh", "TooLow", "Unknown"] } },
+						"additionalProperties": false
+					}
+				]
+			}
+		}
+	});
+	serde_json::to_string_pretty(&schema).expect("schema is valid json")
+}
+
+/// Parsed contents of a single day's cache file
 #[derive(Debug, Serialize, Deserialize, Default)]
-struct Cache {
+pub struct DayCache {
 	parts: HashMap<i32, PartCache>,
 }
 
+impl DayCache {
+	/// Parts which have at least one recorded submission, in ascending order
+	pub fn parts(&self) -> Vec<i32> {
+		let mut parts = self.parts.keys().copied().collect::<Vec<_>>();
+		parts.sort_unstable();
+		parts
+	}
+
+	/// The known correct answer for `part`, if any
+	pub fn correct_answer(&self, part: i32) -> Option<&str> {
+		self.parts.get(&part)?.correct_answer.as_deref()
+	}
+
+	/// Label of the solution which produced the correct answer for `part`, if one was recorded
+	pub fn correct_answer_label(&self, part: i32) -> Option<&str> {
+		let part = self.parts.get(&part)?;
+		part.answers
+			.get(part.correct_answer.as_ref()?)?
+			.label
+			.as_deref()
+	}
+
+	/// Answers for `part` which used to be correct, oldest first
+	pub fn superseded_answers(&self, part: i32) -> &[String] {
+		self.parts
+			.get(&part)
+			.map_or(&[], |p| p.superseded_answers.as_slice())
+	}
+
+	/// Range integer answers for `part` must fall in, as far as "too high" / "too low" responses have shown
+	///
+	/// Both bounds are inclusive
+	pub fn bounds(&self, part: i32) -> (Option<i64>, Option<i64>) {
+		self.parts
+			.get(&part)
+			.map_or((None, None), |p| (p.lower_bound, p.upper_bound))
+	}
+
+	/// Number of distinct answers submitted for `part`
+	pub fn attempts(&self, part: i32) -> usize {
+		self.parts.get(&part).map_or(0, |p| p.answers.len())
+	}
+
+	/// Time left before AoC accepts another answer for `part`, as of the latest rate limit response
+	pub fn cooldown(&self, part: i32) -> Option<std::time::Duration> {
+		self.parts
+			.get(&part)?
+			.answers
+			.values()
+			.filter_map(|response| match &response.response {
+				Err(ErrorSerializable::RateLimit(time)) => {
+					get_remaining_time(&response.submission_time, time)
+				}
+				_ => None,
+			})
+			.max()
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct PartCache {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	correct_answer: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	superseded_answers: Vec<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	lower_bound: Option<i64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	upper_bound: Option<i64>,
 	#[serde(flatten)]
 	answers: HashMap<String, Response>,
 }
 
+impl PartCache {
+	/// Which way `answer` is off if it's an integer outside the bounds learnt from responses which haven't expired
+	fn rule_out(&self, answer: &str, cache_ttl: Option<std::time::Duration>) -> Option<Hint> {
+		let answer = answer.parse::<i64>().ok()?;
+		let (lower_bound, upper_bound) = self.live_bounds(cache_ttl);
+		if lower_bound.is_some_and(|lower| answer < lower) {
+			Some(Hint::TooLow)
+		}
+		else if upper_bound.is_some_and(|upper| answer > upper) {
+			Some(Hint::TooHigh)
+		}
+		else {
+			None
+		}
+	}
+
+	/// The bounds, relearnt from the responses which haven't expired if there is a `cache_ttl`
+	fn live_bounds(&self, cache_ttl: Option<std::time::Duration>) -> (Option<i64>, Option<i64>) {
+		if cache_ttl.is_none() {
+			return (self.lower_bound, self.upper_bound);
+		}
+		let mut live = PartCache::default();
+		for (answer, response) in &self.answers {
+			if let Err(ErrorSerializable::IncorrectWithHint(hint)) = response.response {
+				if !response.expired(cache_ttl) {
+					live.narrow_bounds(answer, hint);
+				}
+			}
+		}
+		(live.lower_bound, live.upper_bound)
+	}
+
+	/// Tightens the bounds after the server said `answer` was too high or too low
+	fn narrow_bounds(&mut self, answer: &str, hint: Hint) {
+		let Ok(answer) = answer.parse::<i64>()
+		else {
+			return;
+		};
+		match hint {
+			Hint::TooHigh => {
+				let upper = answer.saturating_sub(1);
+				self.upper_bound = Some(self.upper_bound.map_or(upper, |u| u.min(upper)));
+			}
+			Hint::TooLow => {
+				let lower = answer.saturating_add(1);
+				self.lower_bound = Some(self.lower_bound.map_or(lower, |l| l.max(lower)));
+			}
+			Hint::Unknown => (),
+		}
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Response {
 	submission_time: DateTime<Utc>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	label: Option<String>,
 	response: std::result::Result<(), ErrorSerializable>,
 }
 
-fn get_remaining_time(submission_time: &DateTime<Utc>, rate_limit_str: &str) -> Option<i64> {
-	let mut remaining_seconds = None;
-	if let Some(ratelimit_seconds) = rate_limit_str
-		.strip_suffix('s')
-		.and_then(|s| s.parse::<i64>().ok())
-		.map(Duration::seconds)
-	{
-		let time_since_ratelimit_response = Utc::now() - *submission_time;
-		remaining_seconds = ratelimit_seconds
-			.checked_sub(&time_since_ratelimit_response)
-			.map(|d| d.num_seconds())
-			.filter(|x| x.is_positive());
+impl Response {
+	/// Whether this is an incorrect response older than `cache_ttl`, which shouldn't be trusted any more
+	fn expired(&self, cache_ttl: Option<std::time::Duration>) -> bool {
+		self.expired_at(cache_ttl, Utc::now())
+	}
+
+	/// Same as `expired`, but as of `now`
+	fn expired_at(&self, cache_ttl: Option<std::time::Duration>, now: DateTime<Utc>) -> bool {
+		let incorrect = matches!(
+			self.response,
+			Err(ErrorSerializable::Incorrect | ErrorSerializable::IncorrectWithHint(_))
+		);
+		let ttl = cache_ttl.and_then(|ttl| Duration::from_std(ttl).ok());
+		incorrect && ttl.is_some_and(|ttl| now - self.submission_time > ttl)
 	}
-	remaining_seconds
+}
+
+fn get_remaining_time(
+	submission_time: &DateTime<Utc>,
+	rate_limit_str: &str,
+) -> Option<std::time::Duration> {
+	let rate_limit = Duration::from_std(duration::parse_human(rate_limit_str).ok()?).ok()?;
+	let time_since_ratelimit_response = Utc::now() - *submission_time;
+	(rate_limit - time_since_ratelimit_response)
+		.to_std()
+		.ok()
+		.filter(|d| !d.is_zero())
 }
 
 /// Checks the local cache for the result.
+/// If `reference_path` is given, it is a read-only cache consulted when the local cache doesn't know the answer,
+///   and it is never written to. If both know a different correct answer, return Err(Error::CacheConflict).
 /// If the local cache has the correct answer already, return Ok(()) if the result is equal to it, or Err(Error::Incorrect) if it is not.
-/// If the local cache contains the result as Ok(()), set the local cache's correct answer to the result and return Ok(()).
-/// If the local cache contains the result as Err(Error::Incorrect), return that.
+///   Unless `revalidation.force_server_check` is set, in which case a different result is always posted, and replaces the correct answer if the server accepts it.
+/// If the local cache contains the result as Ok(()), set the local cache's correct answer to the result and return Ok(AlreadyComplete).
+/// If the local cache contains the result as Err(Error::Incorrect) or Err(Error::IncorrectWithHint), return that.
+///   Unless it is older than `revalidation.cache_ttl`, in which case it is ignored, along with the bounds it taught.
 /// If the local cache contains the result as Err(Error::RateLimit) that was less than 30 seconds ago, return an appropriate rate limit response.
-/// 	TODO/FIXME: keep track of RateLimit for the whole part, not just individual answers.
-/// Else, call the post_fn and add its result to the cache and return it.
-pub fn cache_wrapper(
+///   TODO/FIXME: keep track of RateLimit for the whole part, not just individual answers.
+/// If the result is an integer outside the bounds learnt from earlier "too high" / "too low" responses, return Err(Error::IncorrectWithHint).
+/// Else, call the post_fn and add its result (and `label`, if given) to the cache and return it.
+pub(crate) fn cache_wrapper(
 	cache_path: Option<impl AsRef<Path>>,
+	reference_path: Option<&Path>,
 	part: i32,
 	result: &str,
-	post_fn: impl FnOnce(&str) -> Result<()>,
-) -> Result<()> {
-	if let Some(cache_path) = cache_path {
-		let cache_path = cache_path.as_ref();
-
-		let mut full_cache = std::fs::read_to_string(cache_path)
-			.ok()
-			.and_then(|cache_data| serde_json::from_str::<Cache>(&cache_data).ok())
+	label: Option<&str>,
+	revalidation: Revalidation,
+	post_fn: impl FnOnce(&str) -> Result<SubmissionOutcome>,
+) -> Result<SubmissionOutcome> {
+	let cache_path = cache_path.as_ref().map(AsRef::as_ref);
+	// Keeps other processes from submitting for the same day until this one's result is cached.
+	// Like cache writes, a lock which can't be taken (e.g. the directory is missing) doesn't stop the submission
+	let storage = storage();
+	let _lock = cache_path.and_then(|path| LockGuard::lock(&*storage, path).ok());
+	let pending = PendingSubmission::begin(
+		cache_path,
+		reference_path,
+		part,
+		result,
+		label,
+		revalidation,
+	);
+	match pending {
+		Err(cached) => cached,
+		Ok(pending) => pending.finish(post_fn(result)),
+	}
+}
+
+/// A submission which the cache couldn't answer, so has to be posted - see `cache_wrapper`
+pub(crate) struct PendingSubmission<'a> {
+	cache: Option<(&'a Path, DayCache)>,
+	part: i32,
+	result: &'a str,
+	label: Option<&'a str>,
+	forced: bool,
+}
+
+impl<'a> PendingSubmission<'a> {
+	/// Returns `Err` with the final response if the cache already knows it
+	pub(crate) fn begin(
+		cache_path: Option<&'a Path>,
+		reference_path: Option<&Path>,
+		part: i32,
+		result: &'a str,
+		label: Option<&'a str>,
+		revalidation: Revalidation,
+	) -> std::result::Result<Self, Result<SubmissionOutcome>> {
+		let mut pending = Self {
+			cache: None,
+			part,
+			result,
+			label,
+			forced: false,
+		};
+		let Some(cache_path) = cache_path
+		else {
+			return Ok(pending);
+		};
+
+		let mut full_cache = read_day_cache_or_default(cache_path);
+		let reference = reference_path
+			.map(read_day_cache_or_default)
 			.unwrap_or_default();
+		let reference_part = reference.parts.get(&part);
 
-		let part = full_cache.parts.entry(part).or_default();
+		let part_cache = full_cache.parts.entry(part).or_default();
 
-		if let Some(known_answer) = &part.correct_answer {
-			if result == known_answer {
-				return Ok(());
+		let known_answer = match (
+			&part_cache.correct_answer,
+			reference_part.and_then(|p| p.correct_answer.as_ref()),
+		) {
+			(Some(local), Some(reference)) if local != reference => {
+				return Err(Err(Error::CacheConflict {
+					path: cache_path.to_owned(),
+					reference: reference_path.unwrap_or(cache_path).to_owned(),
+					part,
+				}))
 			}
-			else {
-				return Err(Error::Incorrect);
-			}
-		}
+			(local, reference) => local.as_ref().or(reference),
+		};
 
-		// Deduplicate the same code from three branches below that handles posting the answer to the server and
-		// and handling the result, since its the same for all three cases.
-		// This is not a closure, because `$entry` can either be an `OccupiedEntry` or a `VacantEntry`.
-		macro_rules! post_result_and_handle_response {
-			($entry:ident) => {{
-				let response = post_fn(result);
+		pending.forced = match known_answer {
+			Some(known_answer) if result == known_answer => {
+				return Err(Ok(SubmissionOutcome::AlreadyComplete))
+			}
+			Some(_) if !revalidation.force_server_check => return Err(Err(Error::Incorrect)),
+			Some(_) => true,
+			None => false,
+		};
 
-				let translated = match &response {
-					Ok(()) => {
-						part.correct_answer = Some(result.to_owned());
-						Ok(())
-					}
-					Err(e) => Err(ErrorSerializable::from(e)),
-				};
-
-				$entry.insert(Response {
-					submission_time: Utc::now(),
-					response: translated,
-				});
-				response
-			}};
-		}
-
-		let final_response = match part.answers.entry(result.to_string()) {
-			Entry::Occupied(mut entry) => {
-				let Response {
-					submission_time,
-					response,
-				} = entry.get();
+		if !pending.forced {
+			let live = |response: &&Response| !response.expired(revalidation.cache_ttl);
+			let cached = part_cache.answers.get(result).filter(live).or_else(|| {
+				reference_part
+					.and_then(|p| p.answers.get(result))
+					.filter(live)
+			});
+			if let Some(Response {
+				submission_time,
+				response,
+				..
+			}) = cached
+			{
 				match response {
 					Ok(()) => {
 						// We can only reach here if `part.correct_answer` is `None`
 						// but the JSON had a correct answer, so set `part.correct_answer`
 						// to the correct answer to write to the JSON for future calls.
-						part.correct_answer = Some(result.to_owned());
-						Ok(())
+						part_cache.correct_answer = Some(result.to_owned());
+						write_cache(cache_path, &mut full_cache);
+						return Err(Ok(SubmissionOutcome::AlreadyComplete));
+					}
+					Err(ErrorSerializable::Incorrect) => return Err(Err(Error::Incorrect)),
+					Err(ErrorSerializable::IncorrectWithHint(hint)) => {
+						return Err(Err(Error::IncorrectWithHint(*hint)))
 					}
-					Err(ErrorSerializable::Incorrect) => return Err(Error::Incorrect),
 					Err(ErrorSerializable::RateLimit(time)) => {
-						let remaining_seconds = get_remaining_time(submission_time, time);
-
-						match remaining_seconds {
-							Some(remaining_seconds) => {
-								return Err(Error::RateLimit(format!("{remaining_seconds}s")))
-							}
-							None => post_result_and_handle_response!(entry),
+						if let Some(remaining) = get_remaining_time(submission_time, time) {
+							return Err(Err(Error::RateLimit(RateLimit::new(
+								remaining,
+								Utc::now(),
+							))));
 						}
 					}
-					Err(_err) => post_result_and_handle_response!(entry),
+					Err(_err) => (),
 				}
 			}
-			Entry::Vacant(entry) => post_result_and_handle_response!(entry),
+
+			if let Some(hint) = part_cache.rule_out(result, revalidation.cache_ttl) {
+				return Err(Err(Error::IncorrectWithHint(hint)));
+			}
+		}
+
+		pending.cache = Some((cache_path, full_cache));
+		Ok(pending)
+	}
+
+	/// Records the server's response to the submission in the cache and returns it
+	pub(crate) fn finish(self, response: Result<SubmissionOutcome>) -> Result<SubmissionOutcome> {
+		let Some((cache_path, mut full_cache)) = self.cache
+		else {
+			return response;
 		};
 
-		// If we didn't return early, the cache was modified, so overwrite the file.
-		if let Ok(cache_file) = std::fs::File::options()
-			.truncate(true)
-			.create(true)
-			.write(true)
-			.open(cache_path)
-		{
-			// Ignore cache writing errors
-			let _ = serde_json::to_writer(cache_file, &full_cache);
+		// Nothing was learnt about the answer
+		if matches!(response, Err(Error::NotSubmitted(_))) {
+			return response;
+		}
+
+		// The server doesn't judge answers to completed parts, so a forced check can't overrule the cache
+		if self.forced && matches!(response, Ok(SubmissionOutcome::AlreadyComplete)) {
+			return Err(Error::Incorrect);
 		}
 
-		final_response
+		let part = full_cache.parts.entry(self.part).or_default();
+		let translated = match &response {
+			Ok(_) => {
+				if let Some(superseded) = part.correct_answer.replace(self.result.to_owned()) {
+					part.superseded_answers.push(superseded);
+				}
+				Ok(())
+			}
+			Err(e) => {
+				if let Error::IncorrectWithHint(hint) = e {
+					part.narrow_bounds(self.result, *hint);
+				}
+				Err(ErrorSerializable::from(e))
+			}
+		};
+
+		part.answers.insert(
+			self.result.to_owned(),
+			Response {
+				submission_time: Utc::now(),
+				label: self.label.map(str::to_owned),
+				response: translated,
+			},
+		);
+
+		write_cache(cache_path, &mut full_cache);
+		response
+	}
+}
+
+/// Reads a cache file, treating a missing or unreadable one as empty
+pub(crate) fn read_day_cache_or_default(cache_path: &Path) -> DayCache {
+	read_stored(cache_path)
+		.ok()
+		.and_then(|cache_data| serde_json::from_slice::<DayCache>(&cache_data).ok())
+		.unwrap_or_default()
+}
+
+/// Overwrites the cache file, pruning it first if `AOC_CACHE_MAX_ENTRIES` is set
+fn write_cache(cache_path: &Path, full_cache: &mut DayCache) {
+	if let Some(max_entries) = std::env::var(MAX_ENTRIES_VAR)
+		.ok()
+		.and_then(|max| max.parse().ok())
+	{
+		for part in full_cache.parts.values_mut() {
+			part.prune(PrunePolicy::KeepCorrectAndLastN(max_entries));
+		}
 	}
-	else {
-		post_fn(result)
+
+	// Ignore cache writing errors
+	if let Ok(cache_data) = serde_json::to_vec(&*full_cache) {
+		let _ = write_atomic(cache_path, &cache_data);
+	}
+}
+
+/// Which entries `prune_cache` removes
+///
+/// The correct answer and its entry are never removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrunePolicy {
+	/// Keep the entries for the correct answer plus the `n` most recent other entries of each part
+	KeepCorrectAndLastN(usize),
+	/// Remove rate limit entries whose wait is over
+	DropExpiredRateLimits,
+}
+
+/// What `prune_cache` removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+	/// Part and answer of every removed entry
+	pub removed: Vec<(i32, String)>,
+	/// Number of entries left in the cache
+	pub remaining: usize,
+}
+
+impl PartCache {
+	/// Removes entries according to `policy`, returning the removed answers
+	fn prune(&mut self, policy: PrunePolicy) -> Vec<String> {
+		let correct_answer = self.correct_answer.as_deref();
+		let mut candidates = self
+			.answers
+			.iter()
+			.filter(|(answer, response)| {
+				Some(answer.as_str()) != correct_answer && response.response.is_err()
+			})
+			.collect::<Vec<_>>();
+
+		let mut removed = match policy {
+			PrunePolicy::KeepCorrectAndLastN(n) => {
+				// Newest first, so everything after the first `n` is removed
+				candidates.sort_unstable_by_key(|(_, response)| Reverse(response.submission_time));
+				candidates
+					.into_iter()
+					.skip(n)
+					.map(|(answer, _)| answer.clone())
+					.collect::<Vec<_>>()
+			}
+			PrunePolicy::DropExpiredRateLimits => candidates
+				.into_iter()
+				.filter(|(_, response)| match &response.response {
+					Err(ErrorSerializable::RateLimit(time)) => {
+						get_remaining_time(&response.submission_time, time).is_none()
+					}
+					_ => false,
+				})
+				.map(|(answer, _)| answer.clone())
+				.collect(),
+		};
+		removed.sort_unstable();
+
+		for answer in &removed {
+			self.answers.remove(answer);
+		}
+		removed
+	}
+}
+
+/// Removes entries from the cache file at `path` according to `policy`
+///
+/// The file is rewritten atomically, and left untouched if nothing was removed
+pub fn prune_cache(path: impl AsRef<Path>, policy: PrunePolicy) -> Result<PruneReport> {
+	let path = path.as_ref();
+	let data = read_stored(path)?;
+	let mut cache = serde_json::from_slice::<DayCache>(&data).map_err(|e| Error::InvalidCache {
+		path: path.to_owned(),
+		reason: e.to_string(),
+	})?;
+
+	let mut report = PruneReport::default();
+	for part_number in cache.parts() {
+		let part = cache.parts.get_mut(&part_number).expect("part exists");
+		report.removed.extend(
+			part.prune(policy)
+				.into_iter()
+				.map(|answer| (part_number, answer)),
+		);
+		report.remaining += part.answers.len();
+	}
+
+	if !report.removed.is_empty() {
+		let data = serde_json::to_vec(&cache).expect("cache is always serializable");
+		write_atomic(path, &data)?;
+	}
+	Ok(report)
+}
+
+/// A cache root laid out as `{root}/{year}/{day}.json`, as created by `aoc_magic!`
+#[derive(Debug, Clone)]
+pub struct CacheTree {
+	root: PathBuf,
+	year: Option<i32>,
+}
+
+/// One day's cache file found in a `CacheTree`
+#[derive(Debug)]
+pub struct DayCacheEntry {
+	pub year: i32,
+	pub day: i32,
+	pub path: PathBuf,
+	pub cache: DayCache,
+}
+
+impl CacheTree {
+	/// Opens the cache tree rooted at `root`
+	pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+		let root = root.as_ref().to_path_buf();
+		if storage().list(&root).is_err() {
+			return Err(Error::IO(Some(std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("{} is not a directory", root.display()),
+			))));
+		}
+		Ok(Self { root, year: None })
+	}
+
+	/// Only visit caches for `year`
+	pub fn year(mut self, year: i32) -> Self {
+		self.year = Some(year);
+		self
+	}
+
+	/// Lazily visits each day's cache file, ordered by year then day
+	///
+	/// Files and directories which don't match the layout are skipped.
+	/// Files that can't be read or parsed are yielded as errors without ending the iteration.
+	pub fn iter(&self) -> impl Iterator<Item = Result<DayCacheEntry>> {
+		let years = self.year_dirs().into_iter();
+		CacheTreeIter {
+			years,
+			days: Vec::new().into_iter(),
+		}
+	}
+
+	/// Number of files which `iter` will visit, without parsing them
+	pub fn len_hint(&self) -> usize {
+		self.year_dirs()
+			.into_iter()
+			.map(|(year, dir)| day_files(year, &dir).len())
+			.sum()
+	}
+
+	fn year_dirs(&self) -> Vec<(i32, PathBuf)> {
+		let mut years = numbered_entries(storage().list(&self.root), None)
+			.filter(|(year, _)| self.year.is_none_or(|y| y == *year))
+			.collect::<Vec<_>>();
+		years.sort_unstable_by_key(|(year, _)| *year);
+		years
+	}
+}
+
+struct CacheTreeIter {
+	years: IntoIter<(i32, PathBuf)>,
+	days: IntoIter<(i32, i32, PathBuf)>,
+}
+
+impl Iterator for CacheTreeIter {
+	type Item = Result<DayCacheEntry>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some((year, day, path)) = self.days.next() {
+				return Some(read_day_cache(year, day, path));
+			}
+			let (year, dir) = self.years.next()?;
+			self.days = day_files(year, &dir).into_iter();
+		}
+	}
+}
+
+/// Entries of a directory whose name (minus `extension`) is an integer
+fn numbered_entries(
+	dir: Result<Vec<PathBuf>>,
+	extension: Option<&'static str>,
+) -> impl Iterator<Item = (i32, PathBuf)> {
+	dir.into_iter().flatten().filter_map(move |path| {
+		let stem = match extension {
+			Some(ext) if path.extension()? != ext => return None,
+			Some(_) => path.file_stem()?,
+			None => path.file_name()?,
+		};
+		let number = stem.to_str()?.parse().ok()?;
+		Some((number, path))
+	})
+}
+
+fn day_files(year: i32, dir: &Path) -> Vec<(i32, i32, PathBuf)> {
+	let mut days = numbered_entries(storage().list(dir), Some("json"))
+		.map(|(day, path)| (year, day, path))
+		.collect::<Vec<_>>();
+	days.sort_unstable_by_key(|(_, day, _)| *day);
+	days
+}
+
+fn read_day_cache(year: i32, day: i32, path: PathBuf) -> Result<DayCacheEntry> {
+	let data = read_stored(&path)?;
+	match serde_json::from_slice(&data) {
+		Ok(cache) => Ok(DayCacheEntry {
+			year,
+			day,
+			path,
+			cache,
+		}),
+		Err(e) => Err(Error::InvalidCache {
+			path,
+			reason: e.to_string(),
+		}),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TTL: Option<std::time::Duration> = Some(std::time::Duration::from_secs(3600));
+
+	fn response(age: Duration, response: std::result::Result<(), ErrorSerializable>) -> Response {
+		Response {
+			submission_time: Utc::now() - age,
+			label: None,
+			response,
+		}
+	}
+
+	#[test]
+	fn incorrect_expires_after_ttl() {
+		let incorrect = response(Duration::hours(1), Err(ErrorSerializable::Incorrect));
+		let expires_at = incorrect.submission_time + Duration::hours(1);
+		assert!(!incorrect.expired_at(TTL, expires_at - Duration::seconds(1)));
+		assert!(!incorrect.expired_at(TTL, expires_at));
+		assert!(incorrect.expired_at(TTL, expires_at + Duration::seconds(1)));
+	}
+
+	#[test]
+	fn incorrect_with_hint_expires_after_ttl() {
+		let incorrect = response(
+			Duration::hours(2),
+			Err(ErrorSerializable::IncorrectWithHint(Hint::TooHigh)),
+		);
+		assert!(incorrect.expired(TTL));
+		assert!(!incorrect.expired(None));
+	}
+
+	#[test]
+	fn correct_never_expires() {
+		let correct = response(Duration::days(3650), Ok(()));
+		assert!(!correct.expired(TTL));
+		assert!(!correct.expired(Some(std::time::Duration::ZERO)));
+	}
+
+	#[test]
+	fn rate_limit_is_not_affected() {
+		let rate_limit = response(
+			Duration::hours(2),
+			Err(ErrorSerializable::RateLimit("60s".to_owned())),
+		);
+		assert!(!rate_limit.expired(TTL));
+	}
+
+	#[test]
+	fn expired_hints_dont_bound() {
+		let mut part = PartCache::default();
+		part.narrow_bounds("100", Hint::TooHigh);
+		part.narrow_bounds("10", Hint::TooLow);
+		part.answers.insert(
+			"100".to_owned(),
+			response(
+				Duration::hours(2),
+				Err(ErrorSerializable::IncorrectWithHint(Hint::TooHigh)),
+			),
+		);
+		part.answers.insert(
+			"10".to_owned(),
+			response(
+				Duration::minutes(1),
+				Err(ErrorSerializable::IncorrectWithHint(Hint::TooLow)),
+			),
+		);
+
+		assert_eq!(part.rule_out("150", None), Some(Hint::TooHigh));
+		assert_eq!(part.rule_out("150", TTL), None);
+		assert_eq!(part.rule_out("5", TTL), Some(Hint::TooLow));
 	}
 }
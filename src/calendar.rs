@@ -0,0 +1,148 @@
+//! Puzzle unlock times
+
+use crate::{
+	clock::server_now,
+	error::{Error, Result},
+};
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+
+/// Environment variable which disables the unlock time check (e.g. for testing against a mirror)
+pub const IGNORE_UNLOCK_TIME_VAR: &str = "AOC_IGNORE_UNLOCK_TIME";
+
+/// Year of the first event
+pub const FIRST_YEAR: i32 = 2015;
+
+/// Checks that the puzzle exists, before asking the server for it
+///
+/// `year` must be from 2015 up to this year's event, and `day` within that event (see `days_in_event`).
+/// Returns `Err(Error::InvalidArgument(..))` if not
+pub fn check_puzzle(year: i32, day: i32) -> Result<()> {
+	// The event's year is the year in EST
+	let current_year = (server_now() - TimeDelta::hours(5)).year();
+	if !(FIRST_YEAR..=current_year).contains(&year) {
+		return Err(Error::InvalidArgument(format!(
+			"year {year} is not between {FIRST_YEAR} and {current_year}"
+		)));
+	}
+	let days = days_in_event(year);
+	if !(1..=days).contains(&day) {
+		return Err(Error::InvalidArgument(format!(
+			"day {day} is not between 1 and {days}"
+		)));
+	}
+	Ok(())
+}
+
+/// Checks that `part` is 1 or 2
+///
+/// Returns `Err(Error::InvalidArgument(..))` if not
+pub fn check_part(part: i32) -> Result<()> {
+	match part {
+		1 | 2 => Ok(()),
+		_ => Err(Error::InvalidArgument(format!("part {part} is not 1 or 2"))),
+	}
+}
+
+/// Time at which the puzzle for the given year and day unlocks (midnight EST)
+///
+/// Returns `None` if the date does not exist
+pub fn unlock_time(year: i32, day: i32) -> Option<DateTime<Utc>> {
+	let day = u32::try_from(day).ok()?;
+	// Midnight EST is 05:00 UTC
+	let unlock = NaiveDate::from_ymd_opt(year, 12, day)?.and_hms_opt(5, 0, 0)?;
+	Some(DateTime::from_naive_utc_and_offset(unlock, Utc))
+}
+
+/// Checks that the puzzle for the given year and day has unlocked
+///
+/// Returns `Err(Error::PuzzleNotYetAvailable { .. })` if it unlocks in the future
+///
+/// Always succeeds if the `AOC_IGNORE_UNLOCK_TIME` environment variable is set
+pub fn check_unlocked(year: i32, day: i32) -> Result<()> {
+	if std::env::var_os(IGNORE_UNLOCK_TIME_VAR).is_some() {
+		return Ok(());
+	}
+	let Some(unlocks_at) = unlock_time(year, day)
+	else {
+		return Ok(());
+	};
+	match (unlocks_at - server_now()).to_std() {
+		Ok(remaining) if !remaining.is_zero() => Err(Error::PuzzleNotYetAvailable {
+			unlocks_at,
+			remaining,
+		}),
+		_ => Ok(()),
+	}
+}
+
+/// Number of puzzles in the given year's event (25 until 2024, 12 from 2025)
+pub fn days_in_event(year: i32) -> i32 {
+	if year < 2025 {
+		25
+	}
+	else {
+		12
+	}
+}
+
+/// Year and day of the most recently unlocked puzzle, while an event is running
+///
+/// Returns `Err(Error::NoCurrentPuzzle { .. })` outside of December, or after the event's last day
+pub fn latest_puzzle() -> Result<(i32, i32)> { latest_puzzle_at(server_now()) }
+
+/// Same as `latest_puzzle`, but as of `now`
+pub fn latest_puzzle_at(now: DateTime<Utc>) -> Result<(i32, i32)> {
+	// Puzzles unlock at midnight EST, so the puzzle day is the date in EST
+	let est = now - TimeDelta::hours(5);
+	let (year, day) = (est.year(), est.day() as i32);
+	if est.month() == 12 && day <= days_in_event(year) {
+		return Ok((year, day));
+	}
+
+	let next_year = if est.month() == 12 { year + 1 } else { year };
+	Err(Error::NoCurrentPuzzle {
+		next_unlock: unlock_time(next_year, 1).expect("December 1st exists"),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn current_year() -> i32 { (Utc::now() - TimeDelta::hours(5)).year() }
+
+	fn invalid(result: Result<()>) -> bool { matches!(result, Err(Error::InvalidArgument(_))) }
+
+	#[test]
+	fn years() {
+		assert!(invalid(check_puzzle(2014, 1)));
+		assert!(check_puzzle(2015, 1).is_ok());
+		assert!(check_puzzle(current_year(), 1).is_ok());
+		assert!(invalid(check_puzzle(current_year() + 1, 1)));
+	}
+
+	#[test]
+	fn days_until_2024() {
+		assert!(invalid(check_puzzle(2024, 0)));
+		assert!(check_puzzle(2024, 1).is_ok());
+		assert!(check_puzzle(2024, 25).is_ok());
+		assert!(invalid(check_puzzle(2024, 26)));
+	}
+
+	#[test]
+	fn days_from_2025() {
+		assert!(invalid(check_puzzle(2025, 0)));
+		assert!(check_puzzle(2025, 1).is_ok());
+		assert!(check_puzzle(2025, 12).is_ok());
+		assert!(invalid(check_puzzle(2025, 13)));
+		assert!(invalid(check_puzzle(2025, 25)));
+	}
+
+	#[test]
+	fn parts() {
+		assert!(invalid(check_part(0)));
+		assert!(check_part(1).is_ok());
+		assert!(check_part(2).is_ok());
+		assert!(invalid(check_part(3)));
+	}
+}
@@ -0,0 +1,515 @@
+use crate::{
+	backend::{AocBackend, PoliteDelay, RetryPolicy, SubmissionOutcome, TimedBackend, UreqBackend},
+	calculate_and_post_both_with_options, calculate_and_post_parts_with_options,
+	calculate_and_post_with_options, calculate_only_with_backend, create_data_dir,
+	error::{NotSubmittedReason, Result},
+	example_or_file, input_or_file,
+	session::{MonitoredBackend, Session, SessionInfo, SessionMonitor},
+	submit_cached, BothParts, SubmitOptions, SubmitOutcome,
+};
+use std::{
+	fmt::{self, Debug, Display},
+	io::Write,
+	path::{Path, PathBuf},
+	sync::{Arc, PoisonError, RwLock},
+	time::Duration,
+};
+
+type DryRunHook = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Holds a session and the directories used for caching inputs and submissions
+///
+/// Paths follow the same convention as `aoc_magic!`: `{input_dir}/{year}/{day}.txt` and `{cache_dir}/{year}/{day}.json`
+///
+/// ```rust,no_run
+/// use aoc_driver::*;
+///
+/// fn solution(i: &str) -> String { unimplemented!() }
+///
+/// let session = std::fs::read_to_string(".session.txt").unwrap();
+/// let client = AocClient::new(session).input_dir("inputs").cache_dir("cache");
+/// client.calculate_and_post(2020, 1, Part1, solution).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct AocClient {
+	/// Replaced as a whole by `reload_session`, so requests can hold on to the one they started with without copying it
+	session: Arc<RwLock<Arc<Session>>>,
+	input_dir: PathBuf,
+	cache_dir: PathBuf,
+	reference_cache_dir: Option<PathBuf>,
+	retry: RetryPolicy,
+	timeout: Option<Duration>,
+	rate_limit_wait: Option<Duration>,
+	cache_ttl: Option<Duration>,
+	base_url: Option<String>,
+	polite_delay: Arc<PoliteDelay>,
+	monitor: Arc<SessionMonitor>,
+	dry_run: bool,
+	on_dry_run: Option<DryRunHook>,
+	practice: bool,
+}
+
+impl Debug for AocClient {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("AocClient")
+			.field("session", &"<redacted>")
+			.field("input_dir", &self.input_dir)
+			.field("cache_dir", &self.cache_dir)
+			.field("reference_cache_dir", &self.reference_cache_dir)
+			.field("retry", &self.retry)
+			.field("timeout", &self.timeout)
+			.field("rate_limit_wait", &self.rate_limit_wait)
+			.field("cache_ttl", &self.cache_ttl)
+			.field("base_url", &self.base_url)
+			.field("min_request_interval", &self.polite_delay.interval())
+			.field("monitor", &self.monitor)
+			.field("dry_run", &self.dry_run)
+			.field("on_dry_run", &self.on_dry_run.is_some())
+			.field("practice", &self.practice)
+			.finish()
+	}
+}
+
+impl AocClient {
+	/// Creates a client storing inputs under `inputs` and caches under `cache`
+	pub fn new(session: impl Into<Session>) -> Self {
+		Self {
+			session: Arc::new(RwLock::new(Arc::new(session.into()))),
+			input_dir: PathBuf::from("inputs"),
+			cache_dir: PathBuf::from("cache"),
+			reference_cache_dir: None,
+			retry: RetryPolicy::NONE,
+			timeout: None,
+			rate_limit_wait: None,
+			cache_ttl: None,
+			base_url: None,
+			polite_delay: Arc::default(),
+			monitor: Arc::new(SessionMonitor::new()),
+			dry_run: false,
+			on_dry_run: None,
+			practice: false,
+		}
+	}
+
+	/// Sets the directory inputs are stored in
+	pub fn input_dir(mut self, dir: impl AsRef<Path>) -> Self {
+		self.input_dir = dir.as_ref().to_path_buf();
+		self
+	}
+
+	/// Sets the directory submission caches are stored in
+	pub fn cache_dir(mut self, dir: impl AsRef<Path>) -> Self {
+		self.cache_dir = dir.as_ref().to_path_buf();
+		self
+	}
+
+	/// Sets a directory of read-only caches, laid out like `cache_dir`, consulted when the cache doesn't know the answer
+	///
+	/// Useful for a shared, committed set of known-correct answers. Submissions are only ever recorded in `cache_dir`,
+	/// and a reference cache disagreeing with `cache_dir` on a correct answer is an `Error::CacheConflict`
+	pub fn reference_cache_dir(mut self, dir: impl AsRef<Path>) -> Self {
+		self.reference_cache_dir = Some(dir.as_ref().to_path_buf());
+		self
+	}
+
+	/// Sets how requests which fail because of connection problems or server errors are retried
+	///
+	/// By default they aren't
+	pub fn retry(mut self, retry: RetryPolicy) -> Self {
+		self.retry = retry;
+		self
+	}
+
+	/// Sets how long each request may take before failing with `Error::Timeout`
+	///
+	/// By default there is no limit
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self
+	}
+
+	/// Makes submissions which are rate limited sleep until the limit is over and submit again,
+	/// for up to `max_wait` in total before returning `Error::RateLimit`
+	pub fn wait_on_rate_limit(mut self, max_wait: Duration) -> Self {
+		self.rate_limit_wait = Some(max_wait);
+		self
+	}
+
+	/// Makes cached incorrect responses older than `ttl` be ignored, so those answers are submitted again,
+	/// e.g. after fixing a bug which made a correct answer look wrong
+	///
+	/// Correct answers never expire. By default incorrect ones don't either
+	pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+		self.cache_ttl = Some(ttl);
+		self
+	}
+
+	/// Stops answers from being sent to the server - they are still checked against the cache,
+	/// and answers it doesn't know give `Err(Error::NotSubmitted(NotSubmittedReason::DryRun))`
+	///
+	/// Inputs are still downloaded and cached. See also `calculate_only`
+	pub fn dry_run(mut self, dry_run: bool) -> Self {
+		self.dry_run = dry_run;
+		self
+	}
+
+	/// Calls `hook` with each answer a dry run didn't submit, shown by `render_answer_preview`
+	/// so formatting mistakes like a trailing newline are visible
+	pub fn on_dry_run(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+		self.on_dry_run = Some(Arc::new(hook));
+		self
+	}
+
+	/// Turns on practice mode for this client, for redoing past puzzles without a session
+	///
+	/// Inputs must already be in `input_dir`, and answers are only checked against the cache and reference cache.
+	/// See `practice_mode`
+	pub fn practice(mut self, practice: bool) -> Self {
+		self.practice = practice;
+		self
+	}
+
+	/// Sends requests to `base_url` (e.g. `http://localhost:8080`) instead of adventofcode.com
+	///
+	/// By default `AOC_BASE_URL` is used if it is set
+	pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+		self.base_url = Some(base_url.into());
+		self
+	}
+
+	/// Sets the shortest time between two requests made through this client or its clones, e.g. to slow down a batch run
+	///
+	/// This is on top of the process-wide `set_min_request_interval`. By default it is zero
+	pub fn min_request_interval(mut self, interval: Duration) -> Self {
+		self.polite_delay = Arc::new(PoliteDelay::new(interval));
+		self
+	}
+
+	/// Path the input for the given year and day is stored at
+	pub fn input_path(&self, year: i32, day: i32) -> PathBuf {
+		self.input_dir
+			.join(year.to_string())
+			.join(format!("{day}.txt"))
+	}
+
+	/// Path the example input for the given year and day is stored at, next to the input
+	pub fn example_path(&self, year: i32, day: i32) -> PathBuf {
+		self.input_dir
+			.join(year.to_string())
+			.join(format!("{day}.example.txt"))
+	}
+
+	/// Path the submission cache for the given year and day is stored at
+	pub fn cache_path(&self, year: i32, day: i32) -> PathBuf {
+		self.cache_dir
+			.join(year.to_string())
+			.join(format!("{day}.json"))
+	}
+
+	/// Path the reference cache for the given year and day is read from, if a reference cache is set
+	pub fn reference_cache_path(&self, year: i32, day: i32) -> Option<PathBuf> {
+		let dir = self.reference_cache_dir.as_ref()?;
+		Some(dir.join(year.to_string()).join(format!("{day}.json")))
+	}
+
+	fn submit_options<'p>(
+		&'p self,
+		cache_path: &'p Path,
+		reference_path: Option<&'p Path>,
+	) -> SubmitOptions<'p> {
+		SubmitOptions {
+			reference_path,
+			rate_limit_wait: self.rate_limit_wait,
+			cache_ttl: self.cache_ttl,
+			blocked: match (self.practice, self.dry_run) {
+				(true, _) => Some(NotSubmittedReason::Practice),
+				(false, true) => Some(NotSubmittedReason::DryRun),
+				(false, false) => None,
+			},
+			on_blocked: self
+				.on_dry_run
+				.as_deref()
+				.filter(|_| self.dry_run && !self.practice)
+				.map(|hook| hook as &dyn Fn(&str)),
+			..SubmitOptions::cached_at(Some(cache_path))
+		}
+	}
+
+	/// The session as it is now. Requests made with it aren't affected by `reload_session`
+	fn session(&self) -> Arc<Session> {
+		self.session
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clone()
+	}
+
+	fn backend<'s>(&'s self, session: &'s Session) -> MonitoredBackend<'s, UreqBackend<'s>> {
+		let mut backend = UreqBackend::new(session.expose())
+			.with_retry(self.retry)
+			.with_practice(self.practice)
+			.with_polite_delay(&self.polite_delay);
+		if let Some(base_url) = &self.base_url {
+			backend = backend.with_base_url(base_url);
+		}
+		if let Some(timeout) = self.timeout {
+			backend = backend.with_timeout(timeout);
+		}
+		MonitoredBackend {
+			backend,
+			monitor: &self.monitor,
+		}
+	}
+
+	/// How requests made with the session have gone, shared between clones of this client
+	pub fn session_monitor(&self) -> &SessionMonitor { &self.monitor }
+
+	/// Checks that the session is logged in, e.g. before a run which fetches many inputs
+	///
+	/// See `session::check_session`
+	pub fn check_session(&self) -> Result<SessionInfo> {
+		self.backend(&self.session()).check_session()
+	}
+
+	/// Replaces the session with the contents of the file at `path`, e.g. after it expired
+	///
+	/// The new session is shared with every clone of this client, and used for requests started after this returns.
+	/// Returns `Err(Error::SessionMissing)` if the file is empty (see `Session::from_file`)
+	pub fn reload_session(&self, path: impl AsRef<Path>) -> Result<()> {
+		let session = Session::from_file(path)?;
+		*self.session.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(session);
+		self.monitor.reset();
+		Ok(())
+	}
+
+	/// Gets challenge input, downloading it to `input_path` if it isn't there already
+	pub fn get_input(&self, year: impl Into<i32>, day: impl Into<i32>) -> Result<String> {
+		let (year, day) = (year.into(), day.into());
+		create_data_dir(&self.input_dir, year.to_string())?;
+		input_or_file(
+			&self.backend(&self.session()),
+			year,
+			day,
+			self.input_path(year, day),
+		)
+	}
+
+	/// Gets the input of the first example in the puzzle description, downloading it to `example_path` if it isn't there already
+	///
+	/// See `get_example_or_file`, the example is a best guess
+	pub fn get_example(&self, year: impl Into<i32>, day: impl Into<i32>) -> Result<Option<String>> {
+		let (year, day) = (year.into(), day.into());
+		create_data_dir(&self.input_dir, year.to_string())?;
+		example_or_file(
+			&self.backend(&self.session()).backend,
+			year,
+			day,
+			&self.example_path(year, day),
+		)
+	}
+
+	/// Post an answer to the AoC website, caching the result at `cache_path`
+	///
+	/// See `post_answer`
+	pub fn post_answer(
+		&self,
+		year: impl Into<i32>,
+		day: impl Into<i32>,
+		part: impl Into<i32>,
+		answer: impl Display,
+	) -> Result<()> {
+		let (year, day, part) = (year.into(), day.into(), part.into());
+		let submit = || {
+			create_data_dir(&self.cache_dir, year.to_string())?;
+			let cache_path = self.cache_path(year, day);
+			let reference_path = self.reference_cache_path(year, day);
+			let options = self.submit_options(&cache_path, reference_path.as_deref());
+			submit_cached(
+				&self.backend(&self.session()),
+				year,
+				day,
+				part,
+				options,
+				answer,
+			)
+		};
+		submit()
+			.map(|_| ())
+			.map_err(|e| e.in_challenge(year, day, part))
+	}
+
+	/// Submits `answer` like `post_answer`, returning the outcome and how long the submission took if one was made
+	pub(crate) fn submit_outcome(
+		&self,
+		year: i32,
+		day: i32,
+		part: i32,
+		answer: &str,
+		confirm: Option<&dyn Fn(&str) -> bool>,
+	) -> Result<(SubmitOutcome, Option<Duration>)> {
+		create_data_dir(&self.cache_dir, year.to_string())?;
+		let cache_path = self.cache_path(year, day);
+		let reference_path = self.reference_cache_path(year, day);
+		let options = SubmitOptions {
+			confirm,
+			..self.submit_options(&cache_path, reference_path.as_deref())
+		};
+		let session = self.session();
+		let backend = self.backend(&session);
+		let timed = TimedBackend::new(&backend);
+		let outcome =
+			SubmitOutcome::from_result(submit_cached(&timed, year, day, part, options, answer))?;
+		Ok((outcome, timed.submit_time()))
+	}
+
+	/// Fetches the challenge input, calculate the answer, and post it to the AoC website
+	///
+	/// See `calculate_and_post`
+	pub fn calculate_and_post<SolOutput, SolFn>(
+		&self,
+		year: impl Into<i32>,
+		day: impl Into<i32>,
+		part: impl Into<i32>,
+		solution: SolFn,
+	) -> Result<()>
+	where
+		SolOutput: Display,
+		SolFn: FnOnce(&str) -> SolOutput,
+	{
+		let (year, day) = (year.into(), day.into());
+		create_data_dir(&self.input_dir, year.to_string())?;
+		create_data_dir(&self.cache_dir, year.to_string())?;
+		let cache_path = self.cache_path(year, day);
+		let reference_path = self.reference_cache_path(year, day);
+		calculate_and_post_with_options(
+			&self.backend(&self.session()),
+			year,
+			day,
+			part.into(),
+			Some(self.input_path(year, day)).into(),
+			self.submit_options(&cache_path, reference_path.as_deref()),
+			solution,
+		)
+	}
+
+	/// Fetches the challenge input, then solves and posts part 1 followed by part 2 if part 1 was correct
+	///
+	/// See `calculate_and_post_parts`
+	pub fn calculate_and_post_parts<A, B, Part1Fn, Part2Fn>(
+		&self,
+		year: impl Into<i32>,
+		day: impl Into<i32>,
+		part1: Part1Fn,
+		part2: Part2Fn,
+	) -> Result<()>
+	where
+		A: Display,
+		B: Display,
+		Part1Fn: FnOnce(&str) -> A,
+		Part2Fn: FnOnce(&str) -> B,
+	{
+		let (year, day) = (year.into(), day.into());
+		create_data_dir(&self.input_dir, year.to_string())?;
+		create_data_dir(&self.cache_dir, year.to_string())?;
+		let cache_path = self.cache_path(year, day);
+		let reference_path = self.reference_cache_path(year, day);
+		calculate_and_post_parts_with_options(
+			&self.backend(&self.session()),
+			year,
+			day,
+			Some(self.input_path(year, day)).into(),
+			self.submit_options(&cache_path, reference_path.as_deref()),
+			part1,
+			part2,
+		)
+	}
+
+	/// Fetches the challenge input and runs the solution on it, returning the answer without submitting it
+	///
+	/// See `calculate_only`
+	pub fn calculate_only<SolOutput, SolFn>(
+		&self,
+		year: impl Into<i32>,
+		day: impl Into<i32>,
+		solution: SolFn,
+	) -> Result<String>
+	where
+		SolOutput: Display,
+		SolFn: FnOnce(&str) -> SolOutput,
+	{
+		let (year, day) = (year.into(), day.into());
+		create_data_dir(&self.input_dir, year.to_string())?;
+		calculate_only_with_backend(
+			&self.backend(&self.session()),
+			year,
+			day,
+			Some(self.input_path(year, day)).into(),
+			solution,
+		)
+	}
+
+	/// Fetches the challenge input, calculates the answers to both parts at once, and posts them to the AoC website
+	///
+	/// See `calculate_and_post_both`
+	pub fn calculate_and_post_both<A, B, SolFn>(
+		&self,
+		year: impl Into<i32>,
+		day: impl Into<i32>,
+		solution: SolFn,
+	) -> Result<BothParts>
+	where
+		A: Display,
+		B: Display,
+		SolFn: FnOnce(&str) -> (A, B),
+	{
+		let (year, day) = (year.into(), day.into());
+		create_data_dir(&self.input_dir, year.to_string())?;
+		create_data_dir(&self.cache_dir, year.to_string())?;
+		let cache_path = self.cache_path(year, day);
+		let reference_path = self.reference_cache_path(year, day);
+		calculate_and_post_both_with_options(
+			&self.backend(&self.session()),
+			year,
+			day,
+			Some(self.input_path(year, day)).into(),
+			self.submit_options(&cache_path, reference_path.as_deref()),
+			solution,
+		)
+	}
+}
+
+impl AocBackend for AocClient {
+	fn fetch_input(&self, year: i32, day: i32) -> Result<String> {
+		self.backend(&self.session()).fetch_input(year, day)
+	}
+
+	fn fetch_input_to_writer(&self, year: i32, day: i32, writer: &mut dyn Write) -> Result<u64> {
+		self.backend(&self.session())
+			.fetch_input_to_writer(year, day, writer)
+	}
+
+	fn submit(&self, year: i32, day: i32, part: i32, answer: &str) -> Result<SubmissionOutcome> {
+		self.backend(&self.session())
+			.submit(year, day, part, answer)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn session_is_shared_not_copied() {
+		let client = AocClient::new("c0ffee");
+		let clone = client.clone();
+		let before = client.session();
+		assert!(Arc::ptr_eq(&before, &clone.session()));
+
+		let path = std::env::temp_dir().join(format!("aoc_driver_session_{}", std::process::id()));
+		std::fs::write(&path, "f1a4e\n").unwrap();
+		clone.reload_session(&path).unwrap();
+		let _ = std::fs::remove_file(&path);
+
+		assert_eq!(client.session().expose(), "f1a4e");
+		assert_eq!(before.expose(), "c0ffee");
+	}
+}
@@ -0,0 +1,106 @@
+//! Correcting for a local clock which disagrees with the server's
+//!
+//! The `Date` header of every response is compared with the local time, and unlock times are judged by
+//! `server_now`, which applies the estimated difference
+
+use chrono::{DateTime, TimeDelta, Utc};
+use std::{
+	collections::VecDeque,
+	sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+/// Skew beyond which the `on_clock_skew` hook is called
+pub const SKEW_WARNING_THRESHOLD: TimeDelta = TimeDelta::seconds(60);
+
+/// Skew small enough to be explained by the `Date` header only having whole seconds, which isn't corrected for
+pub const SKEW_TOLERANCE: TimeDelta = TimeDelta::seconds(2);
+
+/// How many recent responses the estimate is based on
+const SAMPLES: usize = 8;
+
+type SkewHook = Arc<dyn Fn(TimeDelta) + Send + Sync>;
+
+struct ClockState {
+	samples: VecDeque<TimeDelta>,
+	warned: bool,
+	on_skew: Option<SkewHook>,
+}
+
+static CLOCK: Mutex<ClockState> = Mutex::new(ClockState {
+	samples: VecDeque::new(),
+	warned: false,
+	on_skew: None,
+});
+
+fn state() -> MutexGuard<'static, ClockState> {
+	CLOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// How far ahead of `local` the server was, given the `Date` header of a response received at `local`
+///
+/// Returns `None` if the header can't be parsed
+pub fn skew_from_date(date: &str, local: DateTime<Utc>) -> Option<TimeDelta> {
+	let server = DateTime::parse_from_rfc2822(date).ok()?;
+	Some(server.with_timezone(&Utc) - local)
+}
+
+/// The median of `samples`, which ignores the odd slow response
+///
+/// Returns `None` if there are no samples
+pub fn estimate_skew(samples: &[TimeDelta]) -> Option<TimeDelta> {
+	let mut sorted = samples.to_vec();
+	sorted.sort_unstable();
+	let middle = sorted.len() / 2;
+	match sorted.len() {
+		0 => None,
+		len if len % 2 == 0 => Some((sorted[middle - 1] + sorted[middle]) / 2),
+		_ => Some(sorted[middle]),
+	}
+}
+
+/// `now` corrected by `skew`, unless it is within `SKEW_TOLERANCE`
+pub fn corrected(now: DateTime<Utc>, skew: Option<TimeDelta>) -> DateTime<Utc> {
+	match skew {
+		Some(skew) if skew.abs() > SKEW_TOLERANCE => now + skew,
+		_ => now,
+	}
+}
+
+/// How far ahead of the local clock the server's is, estimated from recent responses
+///
+/// Returns `None` before any response has been received
+pub fn clock_skew() -> Option<TimeDelta> { estimate_skew(state().samples.make_contiguous()) }
+
+/// The current time according to the server, as far as it is known
+pub fn server_now() -> DateTime<Utc> { corrected(Utc::now(), clock_skew()) }
+
+/// Calls `hook` with the skew the first time it exceeds `SKEW_WARNING_THRESHOLD`, e.g. to suggest syncing the clock
+pub fn on_clock_skew(hook: impl Fn(TimeDelta) + Send + Sync + 'static) {
+	state().on_skew = Some(Arc::new(hook));
+}
+
+/// Notes the `Date` header of a response which was just received
+pub(crate) fn record_server_date(date: &str) {
+	let Some(sample) = skew_from_date(date, Utc::now())
+	else {
+		return;
+	};
+	let mut state = state();
+	state.samples.push_back(sample);
+	if state.samples.len() > SAMPLES {
+		state.samples.pop_front();
+	}
+	let skew = estimate_skew(state.samples.make_contiguous());
+	let hook = match skew {
+		Some(skew) if skew.abs() > SKEW_WARNING_THRESHOLD && !state.warned => {
+			state.warned = true;
+			state.on_skew.clone().map(|hook| (hook, skew))
+		}
+		_ => None,
+	};
+	// Called without the lock held, so the hook can use `clock_skew`
+	drop(state);
+	if let Some((hook, skew)) = hook {
+		hook(skew);
+	}
+}
@@ -0,0 +1,100 @@
+//! The old bool-returning functions, built on the current API, so call sites can be migrated one at a time
+//!
+//! Every error apart from a rate limit is flattened into `false`, as it used to be.
+
+#![allow(deprecated)]
+
+use crate::{
+	error::{Error, Result},
+	get_input_or_file, post_answer,
+};
+use std::{fmt::Display, path::Path};
+
+/// Makes sure the input for that day is stored at `path`, downloading it if needed
+///
+/// Returns whether the input is there
+#[deprecated(
+	note = "use `get_input_or_file`, which returns the input and says why it couldn't be fetched"
+)]
+pub fn get_input_bool(session: &str, year: i32, day: i32, path: impl AsRef<Path>) -> bool {
+	get_input_or_file(session, year, day, path).is_ok()
+}
+
+/// Submits `answer` without a cache
+///
+/// Returns `Ok(true)` if it was correct or the part was already solved, `Ok(false)` if it was wrong or couldn't be submitted,
+/// and `Err(seconds)` with the time left to wait, rounded up, if submitting is rate limited
+#[deprecated(
+	note = "use `post_answer`, which returns `Ok(())` when correct and tells the other errors apart"
+)]
+pub fn post_answer_bool(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	answer: impl Display,
+) -> std::result::Result<bool, u32> {
+	legacy_answer(post_answer(session, year, day, part, None::<&Path>, answer))
+}
+
+/// The legacy return value for the result of `post_answer`
+fn legacy_answer(result: Result<()>) -> std::result::Result<bool, u32> {
+	match result {
+		Ok(()) => Ok(true),
+		Err(e) => match e.inner() {
+			Error::RateLimit(rate_limit) => {
+				let wait = rate_limit.wait;
+				let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+				Err(u32::try_from(secs).unwrap_or(u32::MAX))
+			}
+			_ => Ok(false),
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::error::{Hint, NotSubmittedReason, RateLimit};
+	use chrono::Utc;
+	use std::time::Duration;
+
+	fn rate_limited(wait: Duration) -> Result<()> {
+		Err(Error::RateLimit(RateLimit::new(wait, Utc::now())))
+	}
+
+	#[test]
+	fn outcomes() {
+		assert_eq!(legacy_answer(Ok(())), Ok(true));
+		assert_eq!(legacy_answer(Err(Error::Incorrect)), Ok(false));
+		assert_eq!(
+			legacy_answer(Err(Error::IncorrectWithHint(Hint::TooLow))),
+			Ok(false)
+		);
+		assert_eq!(
+			legacy_answer(Err(Error::NotSubmitted(NotSubmittedReason::CiPolicy))),
+			Ok(false)
+		);
+		assert_eq!(legacy_answer(Err(Error::SessionInvalid)), Ok(false));
+		assert_eq!(legacy_answer(Err(Error::IO(None))), Ok(false));
+	}
+
+	#[test]
+	fn rate_limit_seconds() {
+		assert_eq!(
+			legacy_answer(rate_limited(Duration::from_secs(90))),
+			Err(90)
+		);
+		assert_eq!(
+			legacy_answer(rate_limited(Duration::from_millis(1500))),
+			Err(2)
+		);
+		assert_eq!(
+			legacy_answer(rate_limited(Duration::from_secs(u64::MAX))),
+			Err(u32::MAX)
+		);
+		let in_challenge =
+			rate_limited(Duration::from_secs(60)).map_err(|e| e.in_challenge(2022, 1, 2));
+		assert_eq!(legacy_answer(in_challenge), Err(60));
+	}
+}
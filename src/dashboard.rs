@@ -0,0 +1,278 @@
+//! Read-only terminal dashboard of the caches created by `aoc_magic!`
+//!
+//! Nothing here submits answers. The only request ever made is for your personal times, and only when asked for.
+
+use crate::{
+	cache::CacheTree,
+	duration,
+	error::Result,
+	run_log::{self, RunOutcome, RunRecord},
+	stats::{self, SelfTimes},
+};
+use ratatui::{
+	crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+	layout::{Constraint, Layout},
+	style::{Style, Stylize},
+	widgets::{Block, Paragraph, Row, Table, TableState},
+	DefaultTerminal, Frame,
+};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	io::IsTerminal,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+/// How often the cache tree is reread, which is also how often cooldowns tick down
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Name of the run log looked for in the cache root when `AOC_RUN_LOG` isn't set
+const RUN_LOG_FILE: &str = "run_log.ndjson";
+
+const HEADER: [&str; 6] = ["year", "day", "stars", "attempts", "cooldown", "latest run"];
+
+/// Everything the dashboard shows about one day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayStatus {
+	pub year: i32,
+	pub day: i32,
+	/// Parts solved, according to the cache or your personal times, whichever knows of more
+	pub stars: usize,
+	/// Distinct answers submitted, over both parts
+	pub attempts: usize,
+	/// Longest time left before AoC accepts another answer for either part
+	pub cooldown: Option<Duration>,
+	/// The most recent run log record for the day
+	pub latest_run: Option<RunRecord>,
+}
+
+impl DayStatus {
+	fn new(year: i32, day: i32) -> Self {
+		Self {
+			year,
+			day,
+			stars: 0,
+			attempts: 0,
+			cooldown: None,
+			latest_run: None,
+		}
+	}
+
+	/// The cells of this day's row, in the same order as the header
+	fn columns(&self) -> [String; 6] {
+		[
+			self.year.to_string(),
+			self.day.to_string(),
+			"*".repeat(self.stars),
+			self.attempts.to_string(),
+			self.cooldown
+				.map(duration::format_short)
+				.unwrap_or_default(),
+			self.latest_run
+				.as_ref()
+				.map(describe_run)
+				.unwrap_or_default(),
+		]
+	}
+}
+
+/// e.g. `part 2 correct, solved in 42ms, submitted in 1s`
+fn describe_run(record: &RunRecord) -> String {
+	let outcome = match record.outcome {
+		RunOutcome::Correct => "correct",
+		RunOutcome::Incorrect => "incorrect",
+		RunOutcome::RateLimited => "rate limited",
+		RunOutcome::Failed => "failed",
+	};
+	let mut description = format!(
+		"part {} {outcome}, solved in {}",
+		record.part,
+		duration::format_short(record.solve_time())
+	);
+	if let Some(submit_time) = record.submit_time() {
+		description.push_str(&format!(
+			", submitted in {}",
+			duration::format_short(submit_time)
+		));
+	}
+	description
+}
+
+fn day_status(days: &mut BTreeMap<(i32, i32), DayStatus>, year: i32, day: i32) -> &mut DayStatus {
+	days.entry((year, day))
+		.or_insert_with(|| DayStatus::new(year, day))
+}
+
+/// Assembles one row per day known to the cache tree, the run log or `self_times`, ordered by year then day
+///
+/// Cache files which can't be read are left out rather than hiding every other day
+pub fn collect_status(
+	tree: &CacheTree,
+	runs: &[RunRecord],
+	self_times: &BTreeMap<i32, SelfTimes>,
+) -> Vec<DayStatus> {
+	let mut days = BTreeMap::new();
+	for entry in tree.iter().filter_map(Result::ok) {
+		let parts = entry.cache.parts();
+		let status = day_status(&mut days, entry.year, entry.day);
+		status.stars = parts
+			.iter()
+			.filter(|&&part| entry.cache.correct_answer(part).is_some())
+			.count();
+		status.attempts = parts.iter().map(|&part| entry.cache.attempts(part)).sum();
+		status.cooldown = parts
+			.iter()
+			.filter_map(|&part| entry.cache.cooldown(part))
+			.max();
+	}
+	for (&year, times) in self_times {
+		for (&day, day_times) in &times.days {
+			let solved =
+				usize::from(day_times.part1.is_some()) + usize::from(day_times.part2.is_some());
+			let status = day_status(&mut days, year, day);
+			status.stars = status.stars.max(solved);
+		}
+	}
+	for record in runs {
+		let status = day_status(&mut days, record.year, record.day);
+		if status
+			.latest_run
+			.as_ref()
+			.is_none_or(|latest| latest.timestamp <= record.timestamp)
+		{
+			status.latest_run = Some(record.clone());
+		}
+	}
+	days.into_values().collect()
+}
+
+/// The dashboard as a plain text table, as printed when stdout isn't a terminal
+pub fn render_text(days: &[DayStatus]) -> String {
+	let rows = std::iter::once(HEADER.map(String::from))
+		.chain(days.iter().map(DayStatus::columns))
+		.collect::<Vec<_>>();
+	let mut widths = [0; HEADER.len()];
+	for row in &rows {
+		for (width, cell) in widths.iter_mut().zip(row) {
+			*width = (*width).max(cell.chars().count());
+		}
+	}
+	let mut text = String::new();
+	for row in &rows {
+		let line = row
+			.iter()
+			.zip(widths)
+			.map(|(cell, width)| format!("{cell:<width$}"))
+			.collect::<Vec<_>>()
+			.join("  ");
+		text.push_str(line.trim_end());
+		text.push('\n');
+	}
+	text
+}
+
+/// Shows a live grid of the days cached under `cache_root` until `q` or Ctrl-C is pressed
+///
+/// The cache tree and the run log (`AOC_RUN_LOG`, or `run_log.ndjson` in `cache_root`) are reread every second.
+/// With a `session`, pressing `r` fetches your personal times for the years shown, to count stars earned without this crate.
+/// Nothing is ever submitted.
+///
+/// When stdout isn't a terminal the grid is printed once instead
+pub fn dashboard(cache_root: impl AsRef<Path>, session: Option<&str>) -> Result<()> {
+	let cache_root = cache_root.as_ref();
+	let tree = CacheTree::open(cache_root)?;
+	let run_log_path = std::env::var_os(run_log::RUN_LOG_VAR)
+		.map_or_else(|| cache_root.join(RUN_LOG_FILE), PathBuf::from);
+	// A missing or unreadable run log only leaves the latest run column empty
+	let read_status = |self_times: &BTreeMap<i32, SelfTimes>| {
+		let runs = run_log::read_run_log(&run_log_path).unwrap_or_default();
+		collect_status(&tree, &runs, self_times)
+	};
+
+	if !std::io::stdout().is_terminal() {
+		print!("{}", render_text(&read_status(&BTreeMap::new())));
+		return Ok(());
+	}
+	let mut terminal = ratatui::init();
+	let result = run(&mut terminal, read_status, session);
+	ratatui::restore();
+	result
+}
+
+fn run(
+	terminal: &mut DefaultTerminal,
+	read_status: impl Fn(&BTreeMap<i32, SelfTimes>) -> Vec<DayStatus>,
+	session: Option<&str>,
+) -> Result<()> {
+	let mut self_times = BTreeMap::new();
+	let mut table_state = TableState::default();
+	let mut message = match session {
+		Some(_) => "q: quit  up/down: scroll  r: fetch your personal times",
+		None => "q: quit  up/down: scroll",
+	}
+	.to_owned();
+	loop {
+		let days = read_status(&self_times);
+		terminal.draw(|frame| draw(frame, &days, &mut table_state, &message))?;
+		// Resizes need nothing more than the redraw at the top of the loop
+		if !event::poll(REFRESH_INTERVAL)? {
+			continue;
+		}
+		let Event::Key(key) = event::read()?
+		else {
+			continue;
+		};
+		if key.kind != KeyEventKind::Press {
+			continue;
+		}
+		match key.code {
+			KeyCode::Char('q') => return Ok(()),
+			KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+			KeyCode::Up | KeyCode::Char('k') => table_state.select_previous(),
+			KeyCode::Down | KeyCode::Char('j') => table_state.select_next(),
+			KeyCode::Char('r') => {
+				if let Some(session) = session {
+					message = fetch_self_times(session, &days, &mut self_times);
+				}
+			}
+			_ => (),
+		}
+	}
+}
+
+/// Refetches your personal times for every year shown, returning a line saying how it went
+fn fetch_self_times(
+	session: &str,
+	days: &[DayStatus],
+	self_times: &mut BTreeMap<i32, SelfTimes>,
+) -> String {
+	let years = days.iter().map(|day| day.year).collect::<BTreeSet<_>>();
+	for year in years {
+		match stats::get_self_times(session, year) {
+			Ok(times) => {
+				self_times.insert(year, times);
+			}
+			Err(e) => return format!("couldn't fetch your personal times for {year}: {e}"),
+		}
+	}
+	String::from("fetched your personal times")
+}
+
+fn draw(frame: &mut Frame, days: &[DayStatus], table_state: &mut TableState, message: &str) {
+	let [table_area, message_area] =
+		Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+	let widths = [
+		Constraint::Length(4),
+		Constraint::Length(3),
+		Constraint::Length(5),
+		Constraint::Length(8),
+		Constraint::Length(8),
+		Constraint::Fill(1),
+	];
+	let table = Table::new(days.iter().map(|day| Row::new(day.columns())), widths)
+		.header(Row::new(HEADER).bold())
+		.row_highlight_style(Style::new().reversed())
+		.block(Block::bordered().title(" Advent of Code "));
+	frame.render_stateful_widget(table, table_area, table_state);
+	frame.render_widget(Paragraph::new(message), message_area);
+}
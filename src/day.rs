@@ -0,0 +1,215 @@
+//! Solving a whole day at once: checking the examples, then solving and submitting both parts
+
+use crate::{
+	calendar,
+	error::{Error, Result},
+	log_run, run_solution, run_timed, AocClient, RunTiming, SubmitOutcome,
+};
+use std::{fmt::Display, time::Duration};
+
+type PartFn<'a> = Box<dyn Fn(&str) -> String + 'a>;
+type BothFn<'a> = Box<dyn Fn(&str) -> (String, String) + 'a>;
+type ConfirmFn<'a> = Box<dyn Fn(i32, &str) -> bool + 'a>;
+
+enum Solver<'a> {
+	Parts {
+		part1: PartFn<'a>,
+		part2: Option<PartFn<'a>>,
+	},
+	Both(BothFn<'a>),
+}
+
+/// What `solve_day` runs: the solutions, the examples to check them against first, and whether to ask before submitting
+pub struct DaySpec<'a> {
+	solver: Solver<'a>,
+	/// Part, input and expected answer
+	examples: Vec<(i32, String, String)>,
+	confirm: Option<ConfirmFn<'a>>,
+}
+
+impl<'a> DaySpec<'a> {
+	/// Separate solutions for each part
+	pub fn parts<A: Display, B: Display>(
+		part1: impl Fn(&str) -> A + 'a,
+		part2: impl Fn(&str) -> B + 'a,
+	) -> Self {
+		Self::new(Solver::Parts {
+			part1: Box::new(move |input| part1(input).to_string()),
+			part2: Some(Box::new(move |input| part2(input).to_string())),
+		})
+	}
+
+	/// Only a part 1 solution, e.g. before part 2 is written
+	pub fn part1<A: Display>(part1: impl Fn(&str) -> A + 'a) -> Self {
+		Self::new(Solver::Parts {
+			part1: Box::new(move |input| part1(input).to_string()),
+			part2: None,
+		})
+	}
+
+	/// A solution returning the answers to both parts as a tuple
+	pub fn both<A: Display, B: Display>(solution: impl Fn(&str) -> (A, B) + 'a) -> Self {
+		Self::new(Solver::Both(Box::new(move |input| {
+			let (part1, part2) = solution(input);
+			(part1.to_string(), part2.to_string())
+		})))
+	}
+
+	fn new(solver: Solver<'a>) -> Self {
+		Self {
+			solver,
+			examples: Vec::new(),
+			confirm: None,
+		}
+	}
+
+	/// Checks that the solution to `part` gives `expected` for `input`, before anything is fetched or submitted
+	pub fn example(
+		mut self,
+		part: impl Into<i32>,
+		input: impl Into<String>,
+		expected: impl Display,
+	) -> Self {
+		self.examples
+			.push((part.into(), input.into(), expected.to_string()));
+		self
+	}
+
+	/// Asks `confirm` with the part and the `render_answer_preview` of its answer before it is submitted,
+	/// and doesn't submit it if `confirm` returns `false`
+	///
+	/// Answers the cache already knows are checked without asking
+	pub fn confirm(mut self, confirm: impl Fn(i32, &str) -> bool + 'a) -> Self {
+		self.confirm = Some(Box::new(confirm));
+		self
+	}
+
+	/// The answer to `part` for `input` and how long it took, or `None` if there is no solution for it
+	fn solve(&self, part: i32, input: &str) -> Result<Option<(String, Duration)>> {
+		match (&self.solver, part) {
+			(Solver::Parts { part1, .. }, 1) => run_solution(&**part1, input).map(Some),
+			(
+				Solver::Parts {
+					part2: Some(part2), ..
+				},
+				2,
+			) => run_solution(&**part2, input).map(Some),
+			(Solver::Both(solution), 1 | 2) => {
+				let ((part1, part2), solve_time) = run_timed(|| solution(input))?;
+				Ok(Some((if part == 1 { part1 } else { part2 }, solve_time)))
+			}
+			_ => Ok(None),
+		}
+	}
+}
+
+/// How one part went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartResult {
+	pub answer: String,
+	pub solve_time: Duration,
+	/// Round trip time of the submission, if the answer was submitted rather than found in the cache
+	pub submit_time: Option<Duration>,
+	pub outcome: SubmitOutcome,
+}
+
+/// What `solve_day` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayResult {
+	pub part1: PartResult,
+	/// `None` if there is no part 2 solution, or part 1 wasn't solved
+	pub part2: Option<PartResult>,
+}
+
+/// Checks the examples in `spec`, then solves and submits part 1 followed by part 2 if part 1 was solved
+///
+/// The input is read or downloaded once, and answers go through the client's cache, rate limit and dry run settings.
+/// Wrong answers, rate limits and answers which weren't submitted are outcomes in the result rather than errors
+///
+/// Returns `Err(Error::ExampleFailed { .. })` (in `Error::Challenge`) without touching the network if an example fails
+pub fn solve_day(
+	client: &AocClient,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	spec: &DaySpec,
+) -> Result<DayResult> {
+	let (year, day) = (year.into(), day.into());
+
+	for (index, (part, input, expected)) in spec.examples.iter().enumerate() {
+		let example = index + 1;
+		let check = || match spec.solve(*part, input)? {
+			None => Err(Error::InvalidArgument(format!(
+				"example {example} is for part {part}, which has no solution"
+			))),
+			Some((actual, _)) if actual != *expected => Err(Error::ExampleFailed {
+				example,
+				expected: expected.clone(),
+				actual,
+			}),
+			Some(_) => Ok(()),
+		};
+		check().map_err(|e| e.in_challenge(year, day, *part))?;
+	}
+
+	calendar::check_unlocked(year, day)?;
+	let input = client.get_input(year, day)?;
+
+	let submit = |part: i32, answer: String, timing: RunTiming| -> Result<PartResult> {
+		let confirm = spec
+			.confirm
+			.as_ref()
+			.map(|confirm| move |preview: &str| confirm(part, preview));
+		let confirm = confirm
+			.as_ref()
+			.map(|confirm| confirm as &dyn Fn(&str) -> bool);
+		let (outcome, submit_time) = client.submit_outcome(year, day, part, &answer, confirm)?;
+		let timing = RunTiming {
+			submit_time,
+			..timing
+		};
+		log_run(year, day, part, &answer, timing, &outcome.into_result());
+		Ok(PartResult {
+			answer,
+			solve_time: timing.solve_time,
+			submit_time,
+			outcome,
+		})
+	};
+	let solved = |result: &PartResult| {
+		matches!(
+			result.outcome,
+			SubmitOutcome::Correct | SubmitOutcome::AlreadyComplete
+		)
+	};
+
+	match &spec.solver {
+		Solver::Both(solution) => {
+			let ((answer1, answer2), solve_time) =
+				run_timed(|| solution(&input)).map_err(|e| e.in_challenge(year, day, 1))?;
+			let timing = RunTiming {
+				shared_solve: true,
+				..RunTiming::solved(solve_time)
+			};
+			let part1 = submit(1, answer1, timing).map_err(|e| e.in_challenge(year, day, 1))?;
+			let part2 = match solved(&part1) {
+				true => Some(submit(2, answer2, timing).map_err(|e| e.in_challenge(year, day, 2))?),
+				false => None,
+			};
+			Ok(DayResult { part1, part2 })
+		}
+		Solver::Parts { part1, part2 } => {
+			let solve_and_submit = |part, solution: &PartFn| {
+				let (answer, solve_time) = run_solution(&**solution, &input)?;
+				submit(part, answer, RunTiming::solved(solve_time))
+			};
+			let part1 = solve_and_submit(1, part1).map_err(|e| e.in_challenge(year, day, 1))?;
+			let part2 = match (solved(&part1), part2) {
+				(true, Some(part2)) => {
+					Some(solve_and_submit(2, part2).map_err(|e| e.in_challenge(year, day, 2))?)
+				}
+				_ => None,
+			};
+			Ok(DayResult { part1, part2 })
+		}
+	}
+}
@@ -0,0 +1,189 @@
+//! Human readable durations, as used in rate limits, countdowns and timings
+
+use crate::error::{Error, Result};
+use std::time::Duration;
+
+/// Formats a duration compactly, keeping the two most significant units
+///
+/// e.g. `842ms`, `42s`, `1m 23s`, `2h 05m`, `3d 04h`
+pub fn format_short(duration: Duration) -> String {
+	let secs = duration.as_secs();
+	let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+	match (days, hours, mins, secs) {
+		(0, 0, 0, 0) => format!("{}ms", duration.as_millis()),
+		(0, 0, 0, _) => format!("{secs}s"),
+		(0, 0, ..) => format!("{mins}m {secs}s"),
+		(0, ..) => format!("{hours}h {mins:02}m"),
+		_ => format!("{days}d {hours:02}h"),
+	}
+}
+
+/// Parses a human readable duration
+///
+/// Accepts space separated `<number><unit>` pairs (`90s`, `2m`, `1h 30m`, `4m 30s`, `250ms`, `1d`)
+/// as well as the spelled out forms AoC uses (`one minute`, `5 minutes`, `30 seconds`)
+///
+/// Returns `Err(Error::InvalidDuration(String))` if the string isn't understood
+pub fn parse_human(s: &str) -> Result<Duration> {
+	let invalid = || Error::InvalidDuration(s.to_owned());
+
+	let mut total = Duration::ZERO;
+	let mut tokens = s.split_whitespace().peekable();
+	if tokens.peek().is_none() {
+		return Err(invalid());
+	}
+	while let Some(token) = tokens.next() {
+		let split = token
+			.find(|c: char| !c.is_ascii_digit())
+			.unwrap_or(token.len());
+		let (number, unit) = token.split_at(split);
+		let (number, unit) = match (number.is_empty(), unit.is_empty()) {
+			// `30s`
+			(false, false) => (number.parse::<u64>().map_err(|_| invalid())?, unit),
+			// `30 seconds`
+			(false, true) => (
+				number.parse().map_err(|_| invalid())?,
+				tokens.next().ok_or_else(invalid)?,
+			),
+			// `one minute`
+			(true, _) => (
+				word_to_number(token).ok_or_else(invalid)?,
+				tokens.next().ok_or_else(invalid)?,
+			),
+		};
+		let duration = unit_duration(unit.trim_end_matches([',', '.']))
+			.ok_or_else(invalid)?
+			.checked_mul(u32::try_from(number).map_err(|_| invalid())?)
+			.ok_or_else(invalid)?;
+		total = total.checked_add(duration).ok_or_else(invalid)?;
+	}
+	Ok(total)
+}
+
+fn unit_duration(unit: &str) -> Option<Duration> {
+	let secs = match unit {
+		"ms" | "millisecond" | "milliseconds" => return Some(Duration::from_millis(1)),
+		"s" | "sec" | "secs" | "second" | "seconds" => 1,
+		"m" | "min" | "mins" | "minute" | "minutes" => 60,
+		"h" | "hr" | "hrs" | "hour" | "hours" => 3600,
+		"d" | "day" | "days" => 86400,
+		_ => return None,
+	};
+	Some(Duration::from_secs(secs))
+}
+
+fn word_to_number(word: &str) -> Option<u64> {
+	let number = match word {
+		"a" | "an" | "one" => 1,
+		"two" => 2,
+		"three" => 3,
+		"four" => 4,
+		"five" => 5,
+		"six" => 6,
+		"seven" => 7,
+		"eight" => 8,
+		"nine" => 9,
+		"ten" => 10,
+		_ => return None,
+	};
+	Some(number)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SEC: u64 = 1;
+	const MIN: u64 = 60 * SEC;
+	const HOUR: u64 = 60 * MIN;
+	const DAY: u64 = 24 * HOUR;
+
+	#[test]
+	fn format() {
+		let table = [
+			(Duration::ZERO, "0ms"),
+			(Duration::from_millis(842), "842ms"),
+			(Duration::from_millis(1500), "1s"),
+			(Duration::from_secs(42), "42s"),
+			(Duration::from_secs(MIN), "1m 0s"),
+			(Duration::from_secs(MIN + 23), "1m 23s"),
+			(Duration::from_secs(59 * MIN + 59), "59m 59s"),
+			(Duration::from_secs(2 * HOUR + 5 * MIN + 59), "2h 05m"),
+			(Duration::from_secs(DAY), "1d 00h"),
+			(Duration::from_secs(3 * DAY + 4 * HOUR + 59 * MIN), "3d 04h"),
+		];
+		for (duration, expected) in table {
+			assert_eq!(format_short(duration), expected, "{duration:?}");
+		}
+	}
+
+	#[test]
+	fn parse() {
+		let table = [
+			// Phrasings AoC has been seen to use
+			("1m 30s", MIN + 30),
+			("56s", 56),
+			("one minute", MIN),
+			("5 minutes", 5 * MIN),
+			("30 seconds", 30),
+			("a minute", MIN),
+			// Shorthands
+			("90s", 90),
+			("2m", 2 * MIN),
+			("1h 30m", HOUR + 30 * MIN),
+			("2h 05m", 2 * HOUR + 5 * MIN),
+			("1d", DAY),
+			("  4m\t30s ", 4 * MIN + 30),
+			("1 hour, 2 minutes.", HOUR + 2 * MIN),
+		];
+		for (text, secs) in table {
+			assert_eq!(
+				parse_human(text).unwrap(),
+				Duration::from_secs(secs),
+				"{text:?}"
+			);
+		}
+		assert_eq!(parse_human("250ms").unwrap(), Duration::from_millis(250));
+	}
+
+	#[test]
+	fn round_trip() {
+		for duration in [
+			Duration::from_millis(842),
+			Duration::from_secs(42),
+			Duration::from_secs(MIN + 23),
+			Duration::from_secs(2 * HOUR + 5 * MIN),
+			Duration::from_secs(3 * DAY + 4 * HOUR),
+		] {
+			assert_eq!(parse_human(&format_short(duration)).unwrap(), duration);
+		}
+	}
+
+	#[test]
+	fn invalid() {
+		for text in [
+			"",
+			"   ",
+			"5",
+			"s",
+			"5 parsecs",
+			"-5s",
+			"1.5h",
+			"one",
+			"eleven minutes",
+			"99999999999999999h",
+			"4294967296s",
+		] {
+			assert!(
+				matches!(parse_human(text), Err(Error::InvalidDuration(ref s)) if s == text),
+				"{text:?}"
+			);
+		}
+	}
+
+	#[test]
+	fn overflow() {
+		let huge = "4294967295d ".repeat(60_000);
+		assert!(matches!(parse_human(&huge), Err(Error::InvalidDuration(_))));
+	}
+}
@@ -1,4 +1,6 @@
-use std::any::Any;
+use crate::duration;
+use chrono::{DateTime, Local, Utc};
+use std::{any::Any, fmt, path::PathBuf, time::Duration};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -6,31 +8,259 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug, Error)]
 // #[cfg_attr(feature = "local_cache", derive(serde::Serialize, serde::Deserialize))]
 pub enum Error {
-	#[error("io error")]
-	IO(Option<std::io::Error>),
-	#[error("ureq error")]
-	UReq(Option<Box<ureq::Error>>),
+	#[error("io error{}", cause(.0))]
+	IO(#[source] Option<std::io::Error>),
+	#[error("ureq error{}", cause(.0))]
+	UReq(#[source] Option<Box<ureq::Error>>),
+	#[cfg(feature = "async")]
+	#[error("reqwest error{}", cause(.0))]
+	Reqwest(#[source] Option<Box<reqwest::Error>>),
+	#[error("request timed out")]
+	Timeout,
+	#[error("answer was not submitted - {0}")]
+	NotSubmitted(NotSubmittedReason),
 	#[error("answer was incorrect")]
 	Incorrect,
-	#[error("rate limited - wait {0}")]
-	RateLimit(String),
+	#[error("answer was incorrect - {0}")]
+	IncorrectWithHint(Hint),
+	#[error("rate limited - {0}")]
+	RateLimit(RateLimit),
 	#[error("the solution function panicked")]
 	Panic(Option<Box<dyn Any + Send + 'static>>),
+	#[error("expected an integer answer, got {}", crate::render_answer_preview(.0))]
+	NonIntegerAnswer(String),
+	#[error("invalid argument: {0}")]
+	InvalidArgument(String),
+	#[error("example {example} gave {actual:?}, expected {expected:?}")]
+	ExampleFailed {
+		example: usize,
+		expected: String,
+		actual: String,
+	},
+	#[error("solution gave different answers on the same input: {first:?} then {second:?}")]
+	NonDeterministicAnswer { first: String, second: String },
+	#[error("puzzle unlocks at {unlocks_at} (in {})", crate::duration::format_short(*.remaining))]
+	PuzzleNotYetAvailable {
+		unlocks_at: DateTime<Utc>,
+		remaining: Duration,
+	},
+	#[error("invalid cache file {}: {reason}", .path.display())]
+	InvalidCache { path: PathBuf, reason: String },
+	#[error("could not understand duration {0:?}")]
+	InvalidDuration(String),
+	#[error("{0:?} is not a valid User-Agent")]
+	InvalidUserAgent(String),
+	#[error("invalid proxy {0}")]
+	InvalidProxy(String),
+	#[error("the server's response could not be read: {0}")]
+	InvalidResponse(String),
+	#[error("the network can't be used in practice mode")]
+	PracticeMode,
+	#[error("session cookie is missing, invalid or expired")]
+	SessionInvalid,
+	#[error(
+		"no session cookie - set the AOC_SESSION environment variable or write it to .session.txt"
+	)]
+	SessionMissing,
+	#[error("puzzle is locked until {unlock}")]
+	PuzzleLocked { unlock: DateTime<Utc> },
+	#[error("no puzzle is currently available - the next one unlocks at {next_unlock}")]
+	NoCurrentPuzzle { next_unlock: DateTime<Utc> },
+	#[error("cache {} disagrees with reference cache {} on the correct answer to part {part}", .path.display(), .reference.display())]
+	CacheConflict {
+		path: PathBuf,
+		reference: PathBuf,
+		part: i32,
+	},
+	/// `source` happened while solving or submitting that puzzle part
+	#[error("{year} day {day} part {part}: {source}")]
+	Challenge {
+		year: i32,
+		day: i32,
+		part: i32,
+		source: Box<Error>,
+	},
+}
+
+impl Error {
+	/// Whether this is `Incorrect` or `IncorrectWithHint`
+	pub fn is_incorrect(&self) -> bool {
+		matches!(self.inner(), Error::Incorrect | Error::IncorrectWithHint(_))
+	}
+
+	/// The error without the `Challenge` context, for matching on
+	pub fn inner(&self) -> &Error {
+		match self {
+			Error::Challenge { source, .. } => source.inner(),
+			e => e,
+		}
+	}
+
+	/// Year, day and part of the puzzle this happened for, if known
+	pub fn challenge(&self) -> Option<(i32, i32, i32)> {
+		match self {
+			Error::Challenge {
+				year, day, part, ..
+			} => Some((*year, *day, *part)),
+			_ => None,
+		}
+	}
+
+	/// Wraps this in `Challenge`, unless it already names a puzzle part
+	pub(crate) fn in_challenge(self, year: i32, day: i32, part: i32) -> Self {
+		match self {
+			Error::Challenge { .. } => self,
+			e => Error::Challenge {
+				year,
+				day,
+				part,
+				source: Box::new(e),
+			},
+		}
+	}
+}
+
+/// Why an answer was checked against the cache but not sent to the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotSubmittedReason {
+	/// Running in CI without `AOC_ALLOW_SUBMIT` set (see `submission_blocked`)
+	CiPolicy,
+	/// The answer to the previous part wasn't accepted, so this part can't be solved yet
+	PreviousPartUnsolved,
+	/// Submitting was turned off with `AocClient::dry_run`
+	DryRun,
+	/// Nothing is submitted in practice mode (see `practice_mode`)
+	Practice,
+	/// The confirmation hook (see `day::DaySpec::confirm`) declined to submit the answer
+	Declined,
+	/// The answer was solved from an input AoC didn't serve (see `InputSpec::is_canonical`), and wasn't confirmed
+	NonCanonicalInput,
+}
+
+impl fmt::Display for NotSubmittedReason {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			NotSubmittedReason::CiPolicy => f.write_str("submitting is disabled in CI"),
+			NotSubmittedReason::PreviousPartUnsolved => {
+				f.write_str("the previous part isn't solved")
+			}
+			NotSubmittedReason::DryRun => f.write_str("this is a dry run"),
+			NotSubmittedReason::Practice => f.write_str(
+				"the cache doesn't know the answer, and nothing is submitted in practice mode",
+			),
+			NotSubmittedReason::Declined => f.write_str("submitting the answer was declined"),
+			NotSubmittedReason::NonCanonicalInput => {
+				f.write_str("the answer wasn't solved from the input AoC serves for this account")
+			}
+		}
+	}
+}
+
+/// Which way a wrong answer was off, according to the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "local_cache", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hint {
+	TooHigh,
+	TooLow,
+	/// The server didn't say
+	Unknown,
+}
+
+impl fmt::Display for Hint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Hint::TooHigh => f.write_str("too high"),
+			Hint::TooLow => f.write_str("too low"),
+			Hint::Unknown => f.write_str("no hint"),
+		}
+	}
+}
+
+/// How long to wait before submitting again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+	/// Time left to wait, as of when the rate limit was observed
+	pub wait: Duration,
+	/// When submitting becomes possible again, if known
+	pub retry_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+	/// A rate limit of `wait`, observed at `now`
+	pub fn new(wait: Duration, now: DateTime<Utc>) -> Self {
+		let retry_at = chrono::Duration::from_std(wait)
+			.ok()
+			.and_then(|wait| now.checked_add_signed(wait));
+		Self { wait, retry_at }
+	}
+}
+
+impl fmt::Display for RateLimit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "wait {}", duration::format_short(self.wait))?;
+		if let Some(retry_at) = self.retry_at {
+			write!(f, " (retry after {})", format_time_of_day(retry_at))?;
+		}
+		Ok(())
+	}
+}
+
+/// Formats a time as `HH:MM:SS local`, or `HH:MM:SS UTC` if there is no local timezone configured
+fn format_time_of_day(time: DateTime<Utc>) -> String {
+	let has_local_timezone =
+		std::env::var_os("TZ").is_some() || std::path::Path::new("/etc/localtime").exists();
+	match has_local_timezone || cfg!(not(unix)) {
+		true => format!("{} local", time.with_timezone(&Local).format("%H:%M:%S")),
+		false => format!("{} UTC", time.format("%H:%M:%S")),
+	}
 }
 
 impl From<std::io::Error> for Error {
 	fn from(error: std::io::Error) -> Self { Error::IO(Some(error)) }
 }
 
+impl From<ureq::Error> for Error {
+	fn from(error: ureq::Error) -> Self { Error::UReq(Some(Box::new(error))) }
+}
+
+/// `": {error}"`, or nothing if there is no underlying error
+fn cause(error: &Option<impl fmt::Display>) -> String {
+	error
+		.as_ref()
+		.map(|error| format!(": {error}"))
+		.unwrap_or_default()
+}
+
+/// Cached form of `Error` - changes here must be reflected in `cache::json_schema`
 #[derive(Debug, Clone)]
 #[cfg(feature = "local_cache")]
 #[cfg_attr(feature = "local_cache", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum ErrorSerializable {
 	IO,
 	UReq,
+	Reqwest,
+	Timeout,
+	NotSubmitted,
 	Incorrect,
+	IncorrectWithHint(Hint),
 	RateLimit(String),
 	Panic,
+	NonIntegerAnswer,
+	NonDeterministicAnswer,
+	InvalidArgument,
+	ExampleFailed,
+	PuzzleNotYetAvailable,
+	InvalidCache,
+	InvalidDuration,
+	InvalidUserAgent,
+	InvalidProxy,
+	InvalidResponse,
+	PracticeMode,
+	SessionInvalid,
+	SessionMissing,
+	PuzzleLocked,
+	NoCurrentPuzzle,
+	CacheConflict,
 }
 
 #[cfg(feature = "local_cache")]
@@ -39,9 +269,31 @@ impl ErrorSerializable {
 		match value {
 			Error::IO(_) => Self::IO,
 			Error::UReq(_) => Self::UReq,
+			#[cfg(feature = "async")]
+			Error::Reqwest(_) => Self::Reqwest,
+			Error::Timeout => Self::Timeout,
+			Error::NotSubmitted(_) => Self::NotSubmitted,
 			Error::Incorrect => Self::Incorrect,
-			Error::RateLimit(s) => Self::RateLimit(s.clone()),
+			Error::IncorrectWithHint(hint) => Self::IncorrectWithHint(*hint),
+			Error::RateLimit(r) => Self::RateLimit(duration::format_short(r.wait)),
 			Error::Panic(_) => Self::Panic,
+			Error::NonIntegerAnswer(_) => Self::NonIntegerAnswer,
+			Error::NonDeterministicAnswer { .. } => Self::NonDeterministicAnswer,
+			Error::InvalidArgument(_) => Self::InvalidArgument,
+			Error::ExampleFailed { .. } => Self::ExampleFailed,
+			Error::PuzzleNotYetAvailable { .. } => Self::PuzzleNotYetAvailable,
+			Error::InvalidCache { .. } => Self::InvalidCache,
+			Error::InvalidDuration(_) => Self::InvalidDuration,
+			Error::InvalidUserAgent(_) => Self::InvalidUserAgent,
+			Error::InvalidProxy(_) => Self::InvalidProxy,
+			Error::InvalidResponse(_) => Self::InvalidResponse,
+			Error::PracticeMode => Self::PracticeMode,
+			Error::SessionInvalid => Self::SessionInvalid,
+			Error::SessionMissing => Self::SessionMissing,
+			Error::PuzzleLocked { .. } => Self::PuzzleLocked,
+			Error::NoCurrentPuzzle { .. } => Self::NoCurrentPuzzle,
+			Error::CacheConflict { .. } => Self::CacheConflict,
+			Error::Challenge { source, .. } => Self::from(source),
 		}
 	}
 }
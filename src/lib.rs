@@ -5,14 +5,14 @@
 //!
 //! The most obvious way to use this library is with the `calculate_and_post` function
 //!
-//! ```rust
+//! ```rust,no_run
 //! use aoc_driver::*;
 //!
 //! fn solution(i: &str) -> String { unimplemented!() }
 //!
 //! let session = std::fs::read_to_string(".session.txt").unwrap();
 //! calculate_and_post(
-//!     session,
+//!     &session,
 //!     2020,
 //!     1,
 //!     Part1,
@@ -24,39 +24,94 @@
 //!
 //! There is an even faster way though using the `aoc_magic` macro
 //!
-//! ```rust
+//! ```rust,no_run
 //! use aoc_driver::*;
 //!
 //! fn solution(i: &str) -> String { unimplemented!() }
 //!
 //! let session = std::fs::read_to_string(".session.txt").unwrap();
-//! aoc_magic!(session, 2020:1:1, solution).unwrap()
+//! aoc_magic!(&session, 2020:1:1, solution).unwrap()
 //! ```
 //!
 //! This macro does the same as the above function call (including creating an `inputs` and `cache` directory), but more concisely
+//!
+//! If the session cookie is left out (`aoc_magic!(2020:1:1, solution)`), it is read from the `AOC_SESSION` environment variable
+//!
+//! During an event, `today` can be used instead of the date (`aoc_magic!(&session, today, 1, solution)`) to solve the most recently unlocked puzzle
+//!
+//! When solving a whole year, an `AocClient` saves passing the session and paths around
+//!
+//! ```rust,no_run
+//! use aoc_driver::*;
+//!
+//! fn solution(i: &str) -> String { unimplemented!() }
+//!
+//! let session = std::fs::read_to_string(".session.txt").unwrap();
+//! let client = AocClient::new(session);
+//! client.calculate_and_post(2020, 1, Part1, solution).unwrap();
+//! ```
 
+#[cfg(feature = "async")]
+mod asynchronous;
+pub mod backend;
 #[cfg(feature = "local_cache")]
-mod cache;
+pub mod bisect;
+#[cfg(feature = "local_cache")]
+pub mod cache;
+pub mod calendar;
+mod client;
+pub mod clock;
+pub mod compat;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+pub mod day;
+pub mod duration;
 pub mod error;
+pub mod puzzle;
+#[cfg(feature = "local_cache")]
+pub mod run_log;
+pub mod session;
+pub mod stats;
+pub mod storage;
+#[cfg(feature = "local_cache")]
+pub mod verify;
 
+#[cfg(feature = "async")]
+pub use asynchronous::{calculate_and_post_async, get_input_async, post_answer_async};
+pub use backend::{set_min_request_interval, set_proxy, set_user_agent};
+pub use client::AocClient;
+#[cfg(feature = "tui")]
+pub use dashboard::dashboard;
 pub use Part::*;
 
 #[cfg(feature = "local_cache")]
 use crate::cache::cache_wrapper;
 
-use crate::error::{Error, Result};
+use crate::{
+	backend::{AocBackend, RetryPolicy, SubmissionOutcome, TimedBackend, UreqBackend},
+	error::{Error, Hint, NotSubmittedReason, RateLimit, Result},
+	storage::Storage,
+};
+use chrono::Utc;
 use std::{
+	collections::HashSet,
 	fmt::Display,
 	fs::File,
-	io::{Read, Write},
-	path::Path,
+	io::Write,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex, PoisonError,
+	},
+	thread,
+	time::{Duration, Instant},
 };
-use ureq::{get, post};
 
 /// Simple way to represent the challenge part
 ///
 /// Converts into `u8`
 pub enum Part {
+	/// Submitting fails with `Error::InvalidArgument` unless this is 1 or 2
 	Part(i32),
 	Part1,
 	Part2,
@@ -72,182 +127,1855 @@ impl From<Part> for i32 {
 	}
 }
 
+/// Where `calculate_and_post` gets the puzzle input from
+///
+/// `Option<impl AsRef<Path>>` converts into this, so `None` is `Default` and `Some(path)` is a downloadable `File`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InputSpec {
+	/// Download the input without storing it
+	#[default]
+	Default,
+	/// Read the input from `path`
+	///
+	/// If the file doesn't exist it is downloaded and stored there, unless `never_download` is set
+	File { path: PathBuf, never_download: bool },
+	/// Use this exact input
+	Literal(String),
+}
+
+impl InputSpec {
+	/// Whether the input is the one AoC serves for this account (rather than a file or string supplied by the user)
+	///
+	/// Answers solved from other inputs are marked in the run log, and are only checked against the cache:
+	/// answers it doesn't know give `Err(Error::NotSubmitted(NotSubmittedReason::NonCanonicalInput))`
+	pub fn is_canonical(&self) -> bool {
+		matches!(
+			self,
+			InputSpec::Default
+				| InputSpec::File {
+					never_download: false,
+					..
+				}
+		)
+	}
+}
+
+impl<P> From<Option<P>> for InputSpec
+where
+	P: AsRef<Path>,
+{
+	fn from(value: Option<P>) -> Self {
+		match value {
+			Some(path) => InputSpec::File {
+				path: path.as_ref().to_path_buf(),
+				never_download: false,
+			},
+			None => InputSpec::Default,
+		}
+	}
+}
+
+/// Environment variable `session_from_env` reads the session cookie from
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// Reads the session cookie from the `AOC_SESSION` environment variable, like `Session::from_env`
+///
+/// Returns `Err(Error::SessionMissing)` if the variable is unset or empty, unless in practice mode (see `practice_mode`),
+/// or `Err(Error::SessionInvalid)` if it isn't a hex cookie
+pub fn session_from_env() -> Result<String> {
+	match session::Session::from_env(SESSION_VAR) {
+		Err(Error::SessionMissing) if practice_mode() => Ok(String::new()),
+		session => session.map(|session| session.expose().to_owned()),
+	}
+}
+
 /// Get some input from the AoC website
+///
+/// Returns `Err(Error::SessionInvalid)` if the session cookie was not accepted
+///
+/// Returns `Err(Error::PuzzleLocked { .. })` if the puzzle hasn't unlocked yet
 pub fn get_input(session: &str, year: impl Into<i32>, day: impl Into<i32>) -> Result<String> {
-	let url = format!(
-		"https://adventofcode.com/{}/day/{}/input",
-		year.into(),
-		day.into()
-	);
-	let cookies = format!("session={}", session);
-	let resp = get(&url)
-		.set("User-Agent", "rust/aoc_driver")
-		.set("Cookie", &cookies)
-		.call()
-		.map_err(|e| Error::UReq(Some(Box::new(e))))?;
+	UreqBackend::new(session).fetch_input(year.into(), day.into())
+}
+
+/// Same as `get_input`, but retries connection problems and server errors according to `retry`
+pub fn get_input_with_retry(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	retry: RetryPolicy,
+) -> Result<String> {
+	UreqBackend::new(session)
+		.with_retry(retry)
+		.fetch_input(year.into(), day.into())
+}
+
+/// `poll_interval` `get_input_when_available` uses if none is given
+pub const UNLOCK_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Same as `get_input`, but waits for the puzzle to unlock first, e.g. for a runner started just before midnight
+///
+/// `progress` is called with the time left until the unlock (zero once it has passed) before each wait.
+/// If the server still says the input is locked after the unlock time, it is asked again at most once every `poll_interval`
+/// (`UNLOCK_POLL_INTERVAL` if `None`)
+///
+/// Returns `Err(Error::PuzzleLocked { .. })` if the input isn't available within `max_wait`
+///
+/// Returns `Err(Error::InvalidArgument(..))` if `poll_interval` is zero
+pub fn get_input_when_available(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	poll_interval: Option<Duration>,
+	max_wait: Duration,
+	mut progress: impl FnMut(Duration),
+) -> Result<String> {
+	let (year, day) = (year.into(), day.into());
+	let poll_interval = poll_interval.unwrap_or(UNLOCK_POLL_INTERVAL);
+	if poll_interval.is_zero() {
+		return Err(Error::InvalidArgument(
+			"the poll interval must be more than zero".to_owned(),
+		));
+	}
+	calendar::check_puzzle(year, day)?;
+	let unlock = calendar::unlock_time(year, day).expect("checked by check_puzzle");
+	let start = Instant::now();
+
+	loop {
+		let until_unlock = (unlock - clock::server_now()).to_std().unwrap_or_default();
+		if until_unlock.is_zero() {
+			break;
+		}
+		if until_unlock > max_wait.saturating_sub(start.elapsed()) {
+			return Err(Error::PuzzleLocked { unlock });
+		}
+		progress(until_unlock);
+		thread::sleep(until_unlock.min(poll_interval));
+	}
+
+	let backend = UreqBackend::new(session);
+	loop {
+		match backend.fetch_input(year, day) {
+			Err(Error::PuzzleLocked { .. }) if start.elapsed() + poll_interval <= max_wait => {
+				progress(Duration::ZERO);
+				thread::sleep(poll_interval);
+			}
+			result => return result,
+		}
+	}
+}
 
-	let mut body = resp.into_string()?;
+/// Get the puzzle description from the AoC website, as HTML
+///
+/// Returns the `<article class="day-desc">` of each part that has been unlocked
+///
+/// Returns `Err(Error::SessionInvalid)` if the session cookie was not accepted
+pub fn get_puzzle(session: &str, year: impl Into<i32>, day: impl Into<i32>) -> Result<String> {
+	let page =
+		UreqBackend::new(session).get_page(&format!("{}/day/{}", year.into(), day.into()))?;
+	Ok(puzzle::extract_articles(&page))
+}
 
-	// Remove trailing newline if one exists
-	if body.ends_with('\n') {
-		body.pop();
+/// Gets the puzzle description as HTML - caching at `path` if required
+///
+/// The stored description is used unless `part` is 2 and it doesn't include part 2 yet,
+/// in which case it is downloaded again (see `puzzle::has_part_two`)
+pub fn get_puzzle_or_file(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	part: impl Into<i32>,
+	path: impl AsRef<Path>,
+) -> Result<String> {
+	let path = path.as_ref();
+	let stored = read_stored(path)
+		.ok()
+		.and_then(|page| String::from_utf8(page).ok());
+	match stored {
+		Some(puzzle)
+			if !puzzle.trim().is_empty() && (part.into() < 2 || puzzle::has_part_two(&puzzle)) =>
+		{
+			Ok(puzzle)
+		}
+		_ => {
+			let puzzle = get_puzzle(session, year, day)?;
+			write_atomic(path, puzzle.as_bytes())?;
+			Ok(puzzle)
+		}
 	}
+}
+
+/// Get the puzzle description from the AoC website, converted to Markdown
+///
+/// See `get_puzzle` and `puzzle::to_markdown`
+pub fn get_puzzle_markdown(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+) -> Result<String> {
+	Ok(puzzle::to_markdown(&get_puzzle(session, year, day)?))
+}
+
+/// Get the examples from the puzzle description on the AoC website
+///
+/// See `puzzle::extract_examples` - the results are a best guess and should be checked
+pub fn get_examples(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+) -> Result<Vec<puzzle::Example>> {
+	Ok(puzzle::extract_examples(&get_puzzle(session, year, day)?))
+}
+
+/// Gets the input of the first example in the puzzle description - caching at `path` if required
+///
+/// This is a best guess: the first `<pre><code>` block is taken as the example input, and it isn't always one
+/// (see `puzzle::extract_examples`). A stored example is never replaced, so a wrong one can be fixed by hand.
+///
+/// Returns `Ok(None)` without storing anything if the description has no code block
+pub fn get_example_or_file(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	path: impl AsRef<Path>,
+) -> Result<Option<String>> {
+	example_or_file(
+		&UreqBackend::new(session),
+		year.into(),
+		day.into(),
+		path.as_ref(),
+	)
+}
 
-	Ok(body)
+pub(crate) fn example_or_file(
+	backend: &UreqBackend,
+	year: i32,
+	day: i32,
+	path: &Path,
+) -> Result<Option<String>> {
+	if let Ok(example) = read_stored_string(path) {
+		return Ok(Some(example));
+	}
+	let page = backend.get_page(&format!("{year}/day/{day}"))?;
+	let articles = puzzle::extract_articles(&page);
+	let Some(example) = puzzle::extract_examples(&articles).into_iter().next()
+	else {
+		return Ok(None);
+	};
+	write_atomic(path, example.input.as_bytes())?;
+	Ok(Some(example.input))
 }
 
 /// Gets challenge input - caching at `path` if required
 ///
 /// Checks `path` to see if input has already been downloaded
 ///
-/// If `path` exists will return the contents
+/// If `path` exists and passes `validate_cached_input` will return the contents
 ///
 /// Otherwise download the input for that day and store at `path`
+///
+/// The input is downloaded to a temporary file which is renamed to `path` once complete,
+/// so an interrupted download never leaves a partial input behind
+///
+/// See `get_input_or_file_with` to control when the input is downloaded again
 pub fn get_input_or_file(
 	session: &str,
 	year: impl Into<i32>,
 	day: impl Into<i32>,
 	path: impl AsRef<Path>,
+) -> Result<String> {
+	input_or_file(&UreqBackend::new(session), year.into(), day.into(), path)
+}
+
+/// Whether the contents of a stored input file look like a real input
+///
+/// Empty (or whitespace only) files and HTML pages, such as an error page which got saved by mistake, are rejected
+/// (see `backend::is_html_page`).
+/// `get_input_or_file` downloads the input again if this fails
+pub fn validate_cached_input(input: &str) -> bool {
+	!input.trim().is_empty() && !backend::is_html_page(input) && !backend::is_logged_out_page(input)
+}
+
+/// When `get_input_or_file_with` downloads the input again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RefreshPolicy {
+	/// Use the stored input whenever there is one
+	UseCache,
+	/// Always download the input, replacing the stored one
+	ForceRefresh,
+	/// Download the input if the stored one fails `validate_cached_input`
+	#[default]
+	RefreshIfInvalid,
+}
+
+/// Environment variable which makes inputs be downloaded again with `RefreshPolicy::ForceRefresh`,
+/// e.g. when `aoc_magic!` is given a corrupted input file
+pub const REFRESH_INPUT_VAR: &str = "AOC_REFRESH_INPUT";
+
+impl RefreshPolicy {
+	/// `ForceRefresh` if `AOC_REFRESH_INPUT` is set, otherwise `RefreshIfInvalid`
+	pub fn from_env() -> Self {
+		match env_flag(REFRESH_INPUT_VAR) {
+			true => Self::ForceRefresh,
+			false => Self::RefreshIfInvalid,
+		}
+	}
+}
+
+/// Same as `get_input_or_file`, but `refresh` decides whether a stored input is used
+///
+/// A refreshed input replaces the file at `path` atomically
+pub fn get_input_or_file_with(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	path: impl AsRef<Path>,
+	refresh: RefreshPolicy,
+) -> Result<String> {
+	input_or_file_with(
+		&UreqBackend::new(session),
+		year.into(),
+		day.into(),
+		path,
+		refresh,
+	)
+}
+
+pub(crate) fn input_or_file(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	path: impl AsRef<Path>,
+) -> Result<String> {
+	input_or_file_with(backend, year, day, path, RefreshPolicy::default())
+}
+
+pub(crate) fn input_or_file_with(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	path: impl AsRef<Path>,
+	refresh: RefreshPolicy,
 ) -> Result<String> {
 	let path = path.as_ref();
-	match File::open(path) {
-		Ok(mut f) => {
-			let mut input = String::new();
-			f.read_to_string(&mut input)?;
+	let stored = match refresh {
+		RefreshPolicy::ForceRefresh => None,
+		_ => read_stored(path)
+			.ok()
+			.and_then(|input| String::from_utf8(input).ok()),
+	};
+	match stored {
+		Some(input) if refresh == RefreshPolicy::UseCache || validate_cached_input(&input) => {
 			Ok(input)
 		}
-		Err(_) => {
-			let input = get_input(session, year, day)?;
-			let mut output_file = File::create(path)?;
-			output_file.write_all(input.as_bytes())?;
-			Ok(input)
+		_ => {
+			write_atomic_with(path, |tmp| {
+				backend.fetch_input_to_writer(year, day, tmp)?;
+				Ok(())
+			})?;
+			read_stored_string(path)
 		}
 	}
 }
 
-/// Post an answer to the AoC website.
+/// Most inputs `prefetch_inputs` downloads at once
+pub const PREFETCH_CONCURRENCY: usize = 4;
+
+/// Downloads the inputs for `days` of `year` to `{dir}/{year}/{day}.txt`, a few at a time
 ///
-/// Will also cache the result / submission at the given path if provided
+/// Days whose file already holds a valid input are skipped, and days which haven't unlocked yet fail
+/// with `Error::PuzzleNotYetAvailable` without a request being made.
+/// Returns the path or error for each day, in the order given
+pub fn prefetch_inputs(
+	session: &str,
+	year: impl Into<i32>,
+	days: impl IntoIterator<Item = u8>,
+	dir: impl AsRef<Path>,
+) -> Vec<Result<PathBuf>> {
+	let year = year.into();
+	let days = days.into_iter().collect::<Vec<_>>();
+	let dir = dir.as_ref();
+	let backend = UreqBackend::new(session);
+	let fetch = |day: i32| {
+		calendar::check_unlocked(year, day)?;
+		let path = create_data_dir(dir, year.to_string())?.join(format!("{day}.txt"));
+		input_or_file(&backend, year, day, &path)?;
+		Ok(path)
+	};
+
+	let next = AtomicUsize::new(0);
+	let results = Mutex::new((0..days.len()).map(|_| None).collect::<Vec<_>>());
+	thread::scope(|scope| {
+		for _ in 0..PREFETCH_CONCURRENCY.min(days.len()) {
+			scope.spawn(|| loop {
+				let i = next.fetch_add(1, Ordering::Relaxed);
+				let Some(&day) = days.get(i)
+				else {
+					break;
+				};
+				let result = fetch(day.into());
+				results.lock().unwrap_or_else(PoisonError::into_inner)[i] = Some(result);
+			});
+		}
+	});
+	results
+		.into_inner()
+		.unwrap_or_else(PoisonError::into_inner)
+		.into_iter()
+		.map(|result| result.expect("every day was fetched"))
+		.collect()
+}
+
+/// Stream some input from the AoC website into `writer`, without holding it all in memory
 ///
-/// Returns `Ok(())` if answer was correct or has already been given
+/// Returns the number of bytes written. The trailing newline is removed, as with `get_input`
 ///
-/// Returns `Err(Error::Incorrect)` if the answer was wrong
+/// Errors are the same as for `get_input`
+pub fn get_input_to_writer(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	mut writer: impl Write,
+) -> Result<u64> {
+	UreqBackend::new(session).fetch_input_to_writer(year.into(), day.into(), &mut writer)
+}
+
+/// Get some input from the AoC website as raw bytes
 ///
-/// Returns `Err(Error::RateLimit(String))` if you are being rate-limited
-pub fn post_answer<SolOutput>(
+/// Same as `get_input`, but doesn't require the input to be UTF-8
+pub fn get_input_bytes(
 	session: &str,
-	year: i32,
-	day: i32,
-	part: i32,
-	#[cfg_attr(not(feature = "local_cache"), allow(unused))] cache_path: Option<impl AsRef<Path>>,
-	answer: SolOutput,
-) -> Result<()>
-where
-	SolOutput: Display,
-{
-	let post_fn = |answer: &str| {
-		let url = format!("https://adventofcode.com/{}/day/{}/answer", year, day);
-		let cookies = format!("session={}", session);
-		let form_level = format!("{}", part);
-		let form = [("level", form_level.as_str()), ("answer", answer)];
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+) -> Result<Vec<u8>> {
+	UreqBackend::new(session).fetch_input_bytes(year.into(), day.into())
+}
+
+/// Gets challenge input as raw bytes - caching at `path` if required
+///
+/// Same as `get_input_or_file`, but doesn't require the input to be UTF-8
+pub fn get_input_or_file_bytes(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	path: impl AsRef<Path>,
+) -> Result<Vec<u8>> {
+	let path = path.as_ref();
+	match read_stored(path) {
+		Ok(input) if validate_cached_input(&String::from_utf8_lossy(&input)) => Ok(input),
+		_ => {
+			let input = get_input_bytes(session, year, day)?;
+			write_atomic(path, &input)?;
+			Ok(input)
+		}
+	}
+}
 
-		let resp = post(&url)
-			.set("User-Agent", "rust/aoc_driver")
-			.set("Cookie", &cookies)
-			.send_form(&form)
-			.map_err(|e| Error::UReq(Some(Box::new(e))))?;
+/// What `replace_input` did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputReplaceReport {
+	/// Size of the previous input, if there was one
+	pub old_bytes: Option<u64>,
+	pub new_bytes: u64,
+	/// Whether the downloaded input differs from the previous one
+	pub changed: bool,
+	/// Where the previous input was backed up to, if it was replaced
+	pub backup: Option<PathBuf>,
+}
 
-		let body = resp.into_string().expect("response was not a string");
+/// Downloads the input for that day again and replaces the file at `path` with it
+///
+/// If the existing file differs it is first backed up as `{file name}.bak-{timestamp}` next to it,
+/// with a timestamp no other backup of it has.
+/// The new input is written to a temporary file and renamed into place, so `path` is never left partially written.
+pub fn replace_input(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	path: impl AsRef<Path>,
+) -> Result<InputReplaceReport> {
+	let path = path.as_ref();
+	let input = get_input(session, year, day)?;
+	let storage = storage::storage();
+	let old = match storage.read(path) {
+		Ok(old) => Some(old),
+		Err(Error::IO(Some(e))) if e.kind() == std::io::ErrorKind::NotFound => None,
+		Err(e) => return Err(e),
+	};
+	let changed = old.as_deref() != Some(input.as_bytes());
 
-		let timeout_msg = "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have ";
-		if let Some(index) = body.find(timeout_msg) {
-			let start = index + timeout_msg.len();
-			let end = body.find(" left to wait.").unwrap();
-			let timeout = String::from(&body[start..end]);
-			return Err(Error::RateLimit(timeout));
+	let mut backup = None;
+	if changed {
+		if let Some(old) = &old {
+			let backup_path = new_backup_path(&*storage, path)?;
+			storage.write_atomic(&backup_path, old)?;
+			backup = Some(backup_path);
 		}
+		storage.write_atomic(path, input.as_bytes())?;
+	}
+
+	Ok(InputReplaceReport {
+		old_bytes: old.map(|old| old.len() as u64),
+		new_bytes: input.len() as u64,
+		changed,
+		backup,
+	})
+}
+
+/// Restores the most recent backup made by `replace_input` for the file at `path`
+///
+/// Returns the path of the backup which was restored. The backup itself is kept
+pub fn restore_input_backup(path: impl AsRef<Path>) -> Result<PathBuf> {
+	let path = path.as_ref();
+	let storage = storage::storage();
+	let prefix = format!("{}.bak-", file_name(path));
+	let newest = storage
+		.list(parent_dir(path))?
+		.into_iter()
+		.filter(|backup| file_name(backup).starts_with(&prefix))
+		.max()
+		.ok_or_else(|| {
+			std::io::Error::new(
+				std::io::ErrorKind::NotFound,
+				format!("no backups of {} found", path.display()),
+			)
+		})?;
+	storage.write_atomic(path, &storage.read(&newest)?)?;
+	Ok(newest)
+}
 
-		let correct = body.contains("That's the right answer!")
-			| body.contains("Did you already complete it?");
-		match correct {
-			true => Ok(()),
-			false => Err(Error::Incorrect),
+/// A path for a new backup of `path` which no existing backup has
+///
+/// The timestamps have nanosecond resolution, so the newest backup still sorts last
+fn new_backup_path(storage: &dyn Storage, path: &Path) -> Result<PathBuf> {
+	let taken = storage
+		.list(parent_dir(path))?
+		.iter()
+		.map(|existing| file_name(existing).into_owned())
+		.collect::<HashSet<_>>();
+	loop {
+		let timestamp = Utc::now().format("%Y%m%dT%H%M%S%.9fZ");
+		let backup = sibling_path(path, &format!(".bak-{timestamp}"));
+		if !taken.contains(&*file_name(&backup)) {
+			return Ok(backup);
 		}
+	}
+}
+
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+	path.file_name().unwrap_or_default().to_string_lossy()
+}
+
+/// The directory `path` is in, which is `.` for a bare file name
+fn parent_dir(path: &Path) -> &Path {
+	match path.parent() {
+		Some(dir) if !dir.as_os_str().is_empty() => dir,
+		_ => Path::new("."),
+	}
+}
+
+/// `path` with `suffix` appended to its file name
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+	let mut name = path.file_name().unwrap_or_default().to_os_string();
+	name.push(suffix);
+	path.with_file_name(name)
+}
+
+/// Replaces the file at `path` with `contents` through the current `Storage`
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+	storage::storage().write_atomic(path, contents)
+}
+
+/// Like `write_atomic`, but `write` produces the contents
+///
+/// If `write` fails `path` is left as it was
+pub(crate) fn write_atomic_with(
+	path: &Path,
+	write: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<()> {
+	storage::storage().write_atomic_with(path, Box::new(write))
+}
+
+/// Reads the file at `path` through the current `Storage`
+pub(crate) fn read_stored(path: &Path) -> Result<Vec<u8>> { storage::storage().read(path) }
+
+/// Like `read_stored`, but the file must be UTF-8
+pub(crate) fn read_stored_string(path: &Path) -> Result<String> {
+	String::from_utf8(read_stored(path)?)
+		.map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.utf8_error()).into())
+}
+
+/// Environment variable which overrides `default_data_dir`
+pub const DATA_DIR_VAR: &str = "AOC_DATA_DIR";
+
+/// Directory shared by every project for inputs and caches, so running from another directory or project reuses them
+///
+/// This is `AOC_DATA_DIR` if it is set, otherwise `aoc_driver` in the platform cache directory
+/// (e.g. `~/.cache/aoc_driver` on Linux, `~/Library/Caches/aoc_driver` on macOS, `%LOCALAPPDATA%\aoc_driver` on Windows).
+/// Falls back to the current directory if there is no home directory
+pub fn default_data_dir() -> PathBuf {
+	if let Some(dir) = std::env::var_os(DATA_DIR_VAR).filter(|dir| !dir.is_empty()) {
+		return PathBuf::from(dir);
+	}
+	let var = |name| {
+		std::env::var_os(name)
+			.filter(|dir| !dir.is_empty())
+			.map(PathBuf::from)
+	};
+	let cache_dir = if cfg!(windows) {
+		var("LOCALAPPDATA")
+	}
+	else if cfg!(target_os = "macos") {
+		var("HOME").map(|home| home.join("Library/Caches"))
+	}
+	else {
+		var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
 	};
+	cache_dir.map_or_else(PathBuf::new, |dir| dir.join("aoc_driver"))
+}
 
-	let answer = answer.to_string();
+/// `{default_data_dir}/inputs/{year}/{day}.txt`, creating its directory if needed
+pub fn default_input_path(year: impl Into<i32>, day: impl Into<i32>) -> Result<PathBuf> {
+	magic_paths(default_data_dir(), year.into(), day.into()).map(|(input_path, _)| input_path)
+}
 
-	#[cfg(feature = "local_cache")]
-	return cache_wrapper(cache_path, part, &answer, post_fn);
+/// `{default_data_dir}/cache/{year}/{day}.json`, creating its directory if needed
+pub fn default_cache_path(year: impl Into<i32>, day: impl Into<i32>) -> Result<PathBuf> {
+	magic_paths(default_data_dir(), year.into(), day.into()).map(|(_, cache_path)| cache_path)
+}
+
+/// Environment variable which stops `create_data_dir` from writing a `.gitignore`
+pub const NO_GITIGNORE_VAR: &str = "AOC_NO_GITIGNORE";
 
-	#[cfg(not(feature = "local_cache"))]
-	return post_fn(&answer);
+/// Creates `root/sub_dir` and returns its path
+///
+/// If `root` doesn't exist yet, a `.gitignore` containing `*` is written into it so inputs and caches don't get committed
+/// (AoC asks for inputs not to be redistributed). An existing `.gitignore` is never overwritten.
+/// Set `AOC_NO_GITIGNORE` to skip this.
+pub fn create_data_dir(root: impl AsRef<Path>, sub_dir: impl AsRef<Path>) -> Result<PathBuf> {
+	let root = root.as_ref();
+	let is_new = !root.exists();
+	let dir = root.join(sub_dir);
+	std::fs::create_dir_all(&dir)?;
+
+	if is_new && std::env::var_os(NO_GITIGNORE_VAR).is_none() {
+		match File::options()
+			.write(true)
+			.create_new(true)
+			.open(root.join(".gitignore"))
+		{
+			Ok(mut gitignore) => gitignore.write_all(b"*\n")?,
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => (),
+			Err(e) => return Err(e.into()),
+		}
+	}
+
+	Ok(dir)
 }
 
-/// Fetches the challenge input, calculate the answer, and post it to the AoC website
+/// Lists files under `inputs/` in the git repository at `repo_root` which are tracked by git
 ///
-/// Will cache the input at `path` if provided
+/// Returns an empty list if `repo_root` is not a git repository. Requires `git` to be installed.
+pub fn check_inputs_not_tracked(repo_root: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+	let repo_root = repo_root.as_ref();
+	if !repo_root.join(".git").exists() {
+		return Ok(Vec::new());
+	}
+	let output = std::process::Command::new("git")
+		.arg("-C")
+		.arg(repo_root)
+		.args(["ls-files", "-z", "--", "inputs"])
+		.output()?;
+	if !output.status.success() {
+		return Err(
+			std::io::Error::other(String::from_utf8_lossy(&output.stderr).into_owned()).into(),
+		);
+	}
+	Ok(output
+		.stdout
+		.split(|&b| b == 0)
+		.filter(|path| !path.is_empty())
+		.map(|path| repo_root.join(String::from_utf8_lossy(path).as_ref()))
+		.collect())
+}
+
+/// Post an answer to the AoC website.
 ///
 /// Will also cache the result / submission at the given path if provided
 ///
 /// Returns `Ok(())` if answer was correct or has already been given
 ///
-/// Returns `Err(Error::Incorrect)` if the answer was wrong
+/// Returns `Err(Error::Incorrect)` if the answer was wrong, or `Err(Error::IncorrectWithHint(..))` if the server said it was too high or too low
 ///
-/// Returns `Err(Error::RateLimit(String))` if you are being rate-limited
-pub fn calculate_and_post<SolOutput, SolFn>(
+/// Returns `Err(Error::RateLimit(..))` if you are being rate-limited
+///
+/// Returns `Err(Error::SessionInvalid)` if the session cookie was not accepted
+///
+/// Errors are wrapped in `Error::Challenge` naming the puzzle part - see `Error::inner`
+///
+/// See `submit_answer` to tell these outcomes apart without matching on errors
+pub fn post_answer<SolOutput>(
 	session: &str,
-	year: impl Into<i32>,
-	day: impl Into<i32>,
-	part: impl Into<i32>,
-	input_path: Option<impl AsRef<Path>>,
+	year: i32,
+	day: i32,
+	part: i32,
 	cache_path: Option<impl AsRef<Path>>,
-	solution: SolFn,
+	answer: SolOutput,
 ) -> Result<()>
 where
 	SolOutput: Display,
-	SolFn: FnOnce(&str) -> SolOutput,
 {
-	let year = year.into();
-	let day = day.into();
-	let part = part.into();
-
-	let input = match input_path {
-		Some(path) => get_input_or_file(session, year, day, path),
-		None => get_input(session, year, day),
-	}?;
-	let answer = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| solution(&input)))
-		.map_err(|err| Error::Panic(Some(err)))?;
-	post_answer(session, year, day, part, cache_path, answer)?;
-	Ok(())
+	submit_answer(session, year, day, part, cache_path, answer)
+		.and_then(SubmitOutcome::into_result)
+		.map_err(|e| e.in_challenge(year, day, part))
 }
 
-/// Magic macro to make AoC even easier
+/// Same as `post_answer`, but retries connection problems and server errors according to `retry`
 ///
-/// Usage: `aoc_magic!(<session cookie>, <year>:<day>:<part>, <solution function>)`
-#[macro_export]
-macro_rules! aoc_magic {
-	($session:expr, $year:literal : $day:literal : $part:literal, $sol:expr) => {{
-		let mut input_path = std::path::PathBuf::from_iter(["inputs", &$year.to_string()]);
-		std::fs::create_dir_all(&input_path).unwrap();
-
-		let file_name = format!("{}.txt", $day);
-		input_path.push(file_name);
-
-		let mut cache_path = std::path::PathBuf::from_iter(["cache", &$year.to_string()]);
-		std::fs::create_dir_all(&cache_path).unwrap();
-
-		let file_name = format!("{}.json", $day);
-		cache_path.push(file_name);
-
-		aoc_driver::calculate_and_post(
-			$session,
-			$year,
-			$day,
-			$part,
-			Some(&input_path),
-			Some(&cache_path),
-			$sol,
+/// Wrong answers and rate limits are never retried
+pub fn post_answer_with_retry<SolOutput>(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	cache_path: Option<impl AsRef<Path>>,
+	answer: SolOutput,
+	retry: RetryPolicy,
+) -> Result<()>
+where
+	SolOutput: Display,
+{
+	submit_cached(
+		&UreqBackend::new(session).with_retry(retry),
+		year,
+		day,
+		part,
+		SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref)),
+		answer,
+	)
+	.map(|_| ())
+	.map_err(|e| e.in_challenge(year, day, part))
+}
+
+/// Same as `post_answer`, but when rate limited sleeps until the limit is over and submits again
+///
+/// Returns `Err(Error::RateLimit(..))` if waiting would take longer than `max_wait` in total
+pub fn post_answer_blocking_wait<SolOutput>(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	cache_path: Option<impl AsRef<Path>>,
+	answer: SolOutput,
+	max_wait: Duration,
+) -> Result<()>
+where
+	SolOutput: Display,
+{
+	let options = SubmitOptions {
+		rate_limit_wait: Some(max_wait),
+		..SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref))
+	};
+	submit_cached(&UreqBackend::new(session), year, day, part, options, answer)
+		.map(|_| ())
+		.map_err(|e| e.in_challenge(year, day, part))
+}
+
+/// What happened to an answer given to `submit_answer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitOutcome {
+	/// The answer was accepted
+	Correct,
+	/// The puzzle part had already been completed, either on the server or according to the cache
+	AlreadyComplete,
+	/// The answer was wrong, and which way it was off if the server said
+	Incorrect(Hint),
+	/// You are being rate-limited, and must wait this long before submitting again
+	RateLimited(Duration),
+	/// The cache didn't know the answer, and it wasn't sent to the server
+	NotSubmitted(NotSubmittedReason),
+}
+
+impl SubmitOutcome {
+	/// `Ok(())` for `Correct` and `AlreadyComplete`, the matching error otherwise
+	pub fn into_result(self) -> Result<()> {
+		match self {
+			Self::Correct | Self::AlreadyComplete => Ok(()),
+			Self::Incorrect(Hint::Unknown) => Err(Error::Incorrect),
+			Self::Incorrect(hint) => Err(Error::IncorrectWithHint(hint)),
+			Self::RateLimited(wait) => Err(Error::RateLimit(RateLimit::new(wait, Utc::now()))),
+			Self::NotSubmitted(reason) => Err(Error::NotSubmitted(reason)),
+		}
+	}
+}
+
+impl SubmitOutcome {
+	/// Turns the errors which are outcomes of a submission into `Ok`
+	pub(crate) fn from_result(result: Result<SubmissionOutcome>) -> Result<Self> {
+		match result {
+			Ok(outcome) => Ok(outcome.into()),
+			Err(Error::Incorrect) => Ok(Self::Incorrect(Hint::Unknown)),
+			Err(Error::IncorrectWithHint(hint)) => Ok(Self::Incorrect(hint)),
+			Err(Error::RateLimit(rate_limit)) => Ok(Self::RateLimited(rate_limit.wait)),
+			Err(Error::NotSubmitted(reason)) => Ok(Self::NotSubmitted(reason)),
+			Err(e) => Err(e),
+		}
+	}
+}
+
+impl From<SubmissionOutcome> for SubmitOutcome {
+	fn from(outcome: SubmissionOutcome) -> Self {
+		match outcome {
+			SubmissionOutcome::Correct => Self::Correct,
+			SubmissionOutcome::AlreadyComplete => Self::AlreadyComplete,
+		}
+	}
+}
+
+/// Post an answer to the AoC website, like `post_answer`
+///
+/// Unlike `post_answer`, wrong answers and rate limits are returned as `Ok(SubmitOutcome::..)`,
+/// and a fresh solve is distinguished from a puzzle that was already complete
+///
+/// Returns `Err(Error::SessionInvalid)` if the session cookie was not accepted
+pub fn submit_answer<SolOutput>(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	cache_path: Option<impl AsRef<Path>>,
+	answer: SolOutput,
+) -> Result<SubmitOutcome>
+where
+	SolOutput: Display,
+{
+	let result = submit_cached(
+		&UreqBackend::new(session),
+		year,
+		day,
+		part,
+		SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref)),
+		answer,
+	);
+	SubmitOutcome::from_result(result)
+}
+
+/// Environment variable which allows submitting answers in CI
+pub const ALLOW_SUBMIT_VAR: &str = "AOC_ALLOW_SUBMIT";
+
+/// Whether answers must not be sent to the server, and why
+///
+/// Submitting is blocked when the `CI` environment variable is set (to anything but `false` or `0`),
+/// unless `AOC_ALLOW_SUBMIT` is also set. Blocked answers are still checked against the cache,
+/// and answers the cache doesn't know give `Err(Error::NotSubmitted(..))`
+///
+/// In practice mode (see `practice_mode`) submitting is always blocked
+pub fn submission_blocked() -> Option<NotSubmittedReason> {
+	if practice_mode() {
+		return Some(NotSubmittedReason::Practice);
+	}
+	(env_flag("CI") && !env_flag(ALLOW_SUBMIT_VAR)).then_some(NotSubmittedReason::CiPolicy)
+}
+
+/// Environment variable which turns on practice mode
+pub const PRACTICE_VAR: &str = "AOC_PRACTICE";
+
+/// Whether the `AOC_PRACTICE` environment variable is set (to anything but `false` or `0`)
+///
+/// Practice mode is for redoing past puzzles without a session: nothing is downloaded or submitted
+/// (requests fail with `Error::PracticeMode`), so inputs must already be stored, and answers are only checked against
+/// the cache and reference cache. Answers they don't know give `Err(Error::NotSubmitted(NotSubmittedReason::Practice))`.
+/// See also `AocClient::practice`
+pub fn practice_mode() -> bool { env_flag(PRACTICE_VAR) }
+
+fn env_flag(var: &str) -> bool {
+	std::env::var(var).is_ok_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+}
+
+/// How `submit_cached` records and retries a submission
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(not(feature = "local_cache"), allow(unused))]
+pub(crate) struct SubmitOptions<'a> {
+	pub(crate) cache_path: Option<&'a Path>,
+	/// Read-only cache consulted after `cache_path`
+	pub(crate) reference_path: Option<&'a Path>,
+	pub(crate) label: Option<&'a str>,
+	/// Longest total time to wait out rate limits for, if they should be waited out at all
+	pub(crate) rate_limit_wait: Option<Duration>,
+	/// Age after which cached incorrect responses are ignored
+	pub(crate) cache_ttl: Option<Duration>,
+	/// Only check answers against the cache, never the server, for this reason
+	pub(crate) blocked: Option<NotSubmittedReason>,
+	/// Called with `render_answer_preview` of each answer which `blocked` stopped from being submitted
+	pub(crate) on_blocked: Option<&'a dyn Fn(&str)>,
+	/// Asked with `render_answer_preview` of each answer before it is sent to the server, which it isn't if this returns `false`
+	pub(crate) confirm: Option<&'a dyn Fn(&str) -> bool>,
+	/// The answer was solved from an input AoC didn't serve, so it is only sent if `confirm` approves it
+	pub(crate) non_canonical_input: bool,
+}
+
+impl<'a> SubmitOptions<'a> {
+	pub(crate) fn cached_at(cache_path: Option<&'a Path>) -> Self {
+		Self {
+			cache_path,
+			..Self::default()
+		}
+	}
+
+	/// Marks the answers as needing confirmation if they are solved from `input` and it isn't canonical
+	pub(crate) fn solving(self, input: &InputSpec) -> Self {
+		Self {
+			non_canonical_input: !input.is_canonical(),
+			..self
+		}
+	}
+}
+
+pub(crate) fn submit_cached<SolOutput>(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	part: i32,
+	options: SubmitOptions,
+	answer: SolOutput,
+) -> Result<SubmissionOutcome>
+where
+	SolOutput: Display,
+{
+	calendar::check_puzzle(year, day)?;
+	calendar::check_part(part)?;
+	let answer = answer.to_string();
+	let submit = || {
+		let post_fn = |answer: &str| {
+			let blocked = options.blocked.or_else(submission_blocked);
+			match blocked {
+				Some(reason) => {
+					if let Some(on_blocked) = options.on_blocked {
+						on_blocked(&render_answer_preview(answer));
+					}
+					Err(Error::NotSubmitted(reason))
+				}
+				None => match options.confirm {
+					Some(confirm) if !confirm(&render_answer_preview(answer)) => {
+						Err(Error::NotSubmitted(NotSubmittedReason::Declined))
+					}
+					None if options.non_canonical_input => {
+						Err(Error::NotSubmitted(NotSubmittedReason::NonCanonicalInput))
+					}
+					_ => backend.submit(year, day, part, answer),
+				},
+			}
+		};
+
+		#[cfg(feature = "local_cache")]
+		return cache_wrapper(
+			options.cache_path,
+			options.reference_path,
+			part,
+			&answer,
+			options.label,
+			cache::Revalidation::from_env(options.cache_ttl),
+			post_fn,
+		);
+
+		#[cfg(not(feature = "local_cache"))]
+		return post_fn(&answer);
+	};
+
+	match options.rate_limit_wait {
+		Some(max_wait) => wait_out_rate_limit(max_wait, submit),
+		None => submit(),
+	}
+}
+
+/// Extra time waited on top of a rate limit, so the retry doesn't arrive a moment too early
+const RATE_LIMIT_MARGIN: Duration = Duration::from_secs(1);
+
+/// Calls `submit` until it isn't rate limited, sleeping for each rate limit
+///
+/// Gives up and returns the rate limit if the total wait would exceed `max_wait`
+fn wait_out_rate_limit<T>(max_wait: Duration, mut submit: impl FnMut() -> Result<T>) -> Result<T> {
+	let mut waited = Duration::ZERO;
+	loop {
+		match submit() {
+			Err(Error::RateLimit(limit)) => {
+				let wait = limit.wait + RATE_LIMIT_MARGIN;
+				if waited + wait > max_wait {
+					return Err(Error::RateLimit(limit));
+				}
+				thread::sleep(wait);
+				waited += wait;
+			}
+			result => return result,
+		}
+	}
+}
+
+/// Longest answer `render_answer_preview` shows in full, in characters
+pub const PREVIEW_MAX_CHARS: usize = 64;
+
+/// Shows exactly what would be sent for `answer`, e.g. `"42\n" (3 bytes, not an integer)`
+///
+/// The answer is quoted with control characters escaped, so stray newlines and the like stand out.
+/// Answers longer than `PREVIEW_MAX_CHARS` are cut short with an ellipsis
+pub fn render_answer_preview(answer: &str) -> String {
+	let mut preview = String::from('"');
+	for c in answer.chars().take(PREVIEW_MAX_CHARS) {
+		match c {
+			'"' | '\\' => {
+				preview.push('\\');
+				preview.push(c);
+			}
+			c if c.is_control() => preview.extend(c.escape_default()),
+			c => preview.push(c),
+		}
+	}
+	preview.push('"');
+
+	let truncated = answer.chars().nth(PREVIEW_MAX_CHARS).is_some();
+	if truncated {
+		preview.push('…');
+	}
+	let integer = match expect_integer(answer) {
+		Ok(()) => "an integer",
+		Err(_) => "not an integer",
+	};
+	let note = match truncated {
+		true => format!(", first {PREVIEW_MAX_CHARS} characters shown"),
+		false => String::new(),
+	};
+	format!("{preview} ({} bytes, {integer}{note})", answer.len())
+}
+
+/// Checks that an answer looks like an integer (`-?[0-9]+`)
+///
+/// Returns `Err(Error::NonIntegerAnswer(String))` if it does not
+pub fn expect_integer(answer: &str) -> Result<()> {
+	let digits = answer.strip_prefix('-').unwrap_or(answer);
+	match !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+		true => Ok(()),
+		false => Err(Error::NonIntegerAnswer(answer.to_owned())),
+	}
+}
+
+/// Post an answer that is expected to be an integer to the AoC website.
+///
+/// Same as `post_answer`, but the answer is checked with `expect_integer` first
+/// so formatting bugs are caught before anything is submitted
+pub fn post_integer_answer<SolOutput>(
+	session: &str,
+	year: i32,
+	day: i32,
+	part: i32,
+	cache_path: Option<impl AsRef<Path>>,
+	answer: SolOutput,
+) -> Result<()>
+where
+	SolOutput: Display,
+{
+	let answer = answer.to_string();
+	expect_integer(&answer).map_err(|e| e.in_challenge(year, day, part))?;
+	post_answer(session, year, day, part, cache_path, answer)
+}
+
+/// Fetches the challenge input, calculate the answer, and post it to the AoC website
+///
+/// Reads the input as described by `input` - a `Some(path)` will cache the input at `path`
+///
+/// Will also cache the result / submission at the given path if provided
+///
+/// Returns `Ok(())` if answer was correct or has already been given
+///
+/// Returns `Err(Error::Incorrect)` if the answer was wrong
+///
+/// Returns `Err(Error::RateLimit(..))` if you are being rate-limited
+///
+/// Returns `Err(Error::PuzzleNotYetAvailable { .. })` without touching the network if the puzzle has not unlocked yet
+/// (see `calendar::check_unlocked`)
+///
+/// Errors are wrapped in `Error::Challenge` naming the puzzle part - see `Error::inner`
+///
+/// If the `AOC_RUN_LOG` environment variable is set, a record of the submission is appended to the file it names
+/// (see `run_log`)
+pub fn calculate_and_post<SolOutput, SolFn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	part: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	calculate_and_post_with_backend(
+		&UreqBackend::new(session),
+		year,
+		day,
+		part,
+		input,
+		cache_path,
+		None,
+		solution,
+	)
+}
+
+/// Same as `calculate_and_post`, but records `label` alongside the submission in the cache
+///
+/// Useful for telling later which solution produced the accepted answer (see `cache::DayCache::correct_answer_label`)
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_and_post_with_label<SolOutput, SolFn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	part: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	label: Option<&str>,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	calculate_and_post_with_backend(
+		&UreqBackend::new(session),
+		year,
+		day,
+		part,
+		input,
+		cache_path,
+		label,
+		solution,
+	)
+}
+
+/// Same as `calculate_and_post`, but runs the solution twice (the second time on another thread)
+/// and only submits if both runs give the same answer
+///
+/// This doubles the solve time, but catches solutions whose answer depends on e.g. `HashMap` iteration order.
+/// Returns `Err(Error::NonDeterministicAnswer { .. })` if the answers differ.
+/// The run log records that the answer was checked this way
+pub fn calculate_and_post_verified<SolOutput, SolFn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	part: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: Fn(&str) -> SolOutput + Sync,
+{
+	calculate_and_post_verified_with_options(
+		&UreqBackend::new(session),
+		year.into(),
+		day.into(),
+		part.into(),
+		input.into(),
+		SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref)),
+		solution,
+	)
+}
+
+/// Same as `calculate_and_post_with_label`, but fetches input and submits answers through `backend`
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_and_post_with_backend<SolOutput, SolFn>(
+	backend: &impl AocBackend,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	part: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	label: Option<&str>,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	calculate_and_post_with_options(
+		backend,
+		year.into(),
+		day.into(),
+		part.into(),
+		input.into(),
+		SubmitOptions {
+			label,
+			..SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref))
+		},
+		solution,
+	)
+}
+
+/// `calculate_and_post_with_backend`, with the submission controlled by `options`
+pub(crate) fn calculate_and_post_with_options<SolOutput, SolFn>(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	part: i32,
+	input: InputSpec,
+	options: SubmitOptions,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	let options = options.solving(&input);
+	let solve_and_submit = || {
+		calendar::check_unlocked(year, day)?;
+
+		let input = resolve_input(backend, year, day, input)?;
+		let (answer, solve_time) = run_solution(solution, &input)?;
+		submit_and_log(
+			backend,
+			year,
+			day,
+			part,
+			options,
+			&answer,
+			RunTiming::solved(solve_time),
 		)
+	};
+	solve_and_submit().map_err(|e| e.in_challenge(year, day, part))
+}
+
+/// `calculate_and_post_verified`, with the submission controlled by `options`
+pub(crate) fn calculate_and_post_verified_with_options<SolOutput, SolFn>(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	part: i32,
+	input: InputSpec,
+	options: SubmitOptions,
+	solution: SolFn,
+) -> Result<()>
+where
+	SolOutput: Display,
+	SolFn: Fn(&str) -> SolOutput + Sync,
+{
+	let options = options.solving(&input);
+	let solve_and_submit = || {
+		calendar::check_unlocked(year, day)?;
+
+		let input = resolve_input(backend, year, day, input)?;
+		let (answer, solve_time) = run_solution_twice(&solution, &input)?;
+		let timing = RunTiming {
+			verified: true,
+			..RunTiming::solved(solve_time)
+		};
+		submit_and_log(backend, year, day, part, options, &answer, timing)
+	};
+	solve_and_submit().map_err(|e| e.in_challenge(year, day, part))
+}
+
+/// Fetches the challenge input (caching it as `input` says) and runs the solution on it, without submitting the answer
+///
+/// Returns the answer
+pub fn calculate_only<SolOutput, SolFn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	solution: SolFn,
+) -> Result<String>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	calculate_only_with_backend(
+		&UreqBackend::new(session),
+		year.into(),
+		day.into(),
+		input.into(),
+		solution,
+	)
+}
+
+pub(crate) fn calculate_only_with_backend<SolOutput, SolFn>(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	input: InputSpec,
+	solution: SolFn,
+) -> Result<String>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	calendar::check_unlocked(year, day)?;
+
+	let input = resolve_input(backend, year, day, input)?;
+	run_solution(solution, &input).map(|(answer, _)| answer)
+}
+
+/// Results of `calculate_and_post_both`, one per part
+#[derive(Debug)]
+pub struct BothParts {
+	pub part1: Result<()>,
+	/// `Err(Error::NotSubmitted(NotSubmittedReason::PreviousPartUnsolved))` (in `Error::Challenge`) if part 1 wasn't solved
+	pub part2: Result<()>,
+}
+
+/// Like `calculate_and_post`, but for a solution which returns the answers to both parts at once
+///
+/// The part 1 answer is posted first, and the part 2 answer only if part 1 was correct or already complete.
+///
+/// Returns `Err` (in `Error::Challenge` for part 1) if the puzzle input couldn't be fetched or the solution panicked,
+/// otherwise the result of each part
+pub fn calculate_and_post_both<A, B, SolFn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	solution: SolFn,
+) -> Result<BothParts>
+where
+	A: Display,
+	B: Display,
+	SolFn: FnOnce(&str) -> (A, B),
+{
+	calculate_and_post_both_with_backend(
+		&UreqBackend::new(session),
+		year,
+		day,
+		input,
+		cache_path,
+		solution,
+	)
+}
+
+/// Same as `calculate_and_post_both`, but fetches input and submits answers through `backend`
+pub fn calculate_and_post_both_with_backend<A, B, SolFn>(
+	backend: &impl AocBackend,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	solution: SolFn,
+) -> Result<BothParts>
+where
+	A: Display,
+	B: Display,
+	SolFn: FnOnce(&str) -> (A, B),
+{
+	calculate_and_post_both_with_options(
+		backend,
+		year.into(),
+		day.into(),
+		input.into(),
+		SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref)),
+		solution,
+	)
+}
+
+/// `calculate_and_post_both`, with the submissions controlled by `options`
+pub(crate) fn calculate_and_post_both_with_options<A, B, SolFn>(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	input: InputSpec,
+	options: SubmitOptions,
+	solution: SolFn,
+) -> Result<BothParts>
+where
+	A: Display,
+	B: Display,
+	SolFn: FnOnce(&str) -> (A, B),
+{
+	let options = options.solving(&input);
+	let ((answer1, answer2), solve_time) = calendar::check_unlocked(year, day)
+		.and_then(|()| resolve_input(backend, year, day, input))
+		.and_then(|input| run_timed(|| solution(&input)))
+		.map_err(|e| e.in_challenge(year, day, 1))?;
+	let timing = RunTiming {
+		shared_solve: true,
+		..RunTiming::solved(solve_time)
+	};
+
+	let part1 = submit_and_log(backend, year, day, 1, options, &answer1.to_string(), timing)
+		.map_err(|e| e.in_challenge(year, day, 1));
+	let part2 = match part1 {
+		Ok(()) => submit_and_log(backend, year, day, 2, options, &answer2.to_string(), timing)
+			.map_err(|e| e.in_challenge(year, day, 2)),
+		Err(_) => Err(
+			Error::NotSubmitted(NotSubmittedReason::PreviousPartUnsolved)
+				.in_challenge(year, day, 2),
+		),
+	};
+	Ok(BothParts { part1, part2 })
+}
+
+/// Like `calculate_and_post`, but for both parts of a day with separate solutions
+///
+/// Part 1 is solved and posted first, and part 2 only if part 1 was correct or already complete.
+/// The input is only fetched once
+///
+/// Returns the first error, in `Error::Challenge` naming the part it happened for
+pub fn calculate_and_post_parts<A, B, Part1Fn, Part2Fn>(
+	session: &str,
+	year: impl Into<i32>,
+	day: impl Into<i32>,
+	input: impl Into<InputSpec>,
+	cache_path: Option<impl AsRef<Path>>,
+	part1: Part1Fn,
+	part2: Part2Fn,
+) -> Result<()>
+where
+	A: Display,
+	B: Display,
+	Part1Fn: FnOnce(&str) -> A,
+	Part2Fn: FnOnce(&str) -> B,
+{
+	calculate_and_post_parts_with_options(
+		&UreqBackend::new(session),
+		year.into(),
+		day.into(),
+		input.into(),
+		SubmitOptions::cached_at(cache_path.as_ref().map(AsRef::as_ref)),
+		part1,
+		part2,
+	)
+}
+
+/// `calculate_and_post_parts`, with the submissions controlled by `options`
+pub(crate) fn calculate_and_post_parts_with_options<A, B, Part1Fn, Part2Fn>(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	input: InputSpec,
+	options: SubmitOptions,
+	part1: Part1Fn,
+	part2: Part2Fn,
+) -> Result<()>
+where
+	A: Display,
+	B: Display,
+	Part1Fn: FnOnce(&str) -> A,
+	Part2Fn: FnOnce(&str) -> B,
+{
+	let options = options.solving(&input);
+	let input = calendar::check_unlocked(year, day)
+		.and_then(|()| resolve_input(backend, year, day, input))
+		.map_err(|e| e.in_challenge(year, day, 1))?;
+
+	run_solution(part1, &input)
+		.and_then(|(answer, solve_time)| {
+			submit_and_log(
+				backend,
+				year,
+				day,
+				1,
+				options,
+				&answer,
+				RunTiming::solved(solve_time),
+			)
+		})
+		.map_err(|e| e.in_challenge(year, day, 1))?;
+	run_solution(part2, &input)
+		.and_then(|(answer, solve_time)| {
+			submit_and_log(
+				backend,
+				year,
+				day,
+				2,
+				options,
+				&answer,
+				RunTiming::solved(solve_time),
+			)
+		})
+		.map_err(|e| e.in_challenge(year, day, 2))
+}
+
+/// Reads, downloads or passes through the puzzle input as `input` says
+pub(crate) fn resolve_input(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	input: InputSpec,
+) -> Result<String> {
+	Ok(match input {
+		InputSpec::Default => backend.fetch_input(year, day)?,
+		InputSpec::File {
+			path,
+			never_download: false,
+		} => input_or_file_with(backend, year, day, path, RefreshPolicy::from_env())?,
+		InputSpec::File {
+			path,
+			never_download: true,
+		} => read_stored_string(&path)?,
+		InputSpec::Literal(input) => input,
+	})
+}
+
+/// Submits an answer and records it in the run log, along with `timing`
+#[allow(clippy::too_many_arguments)]
+fn submit_and_log(
+	backend: &impl AocBackend,
+	year: i32,
+	day: i32,
+	part: i32,
+	options: SubmitOptions,
+	answer: &str,
+	timing: RunTiming,
+) -> Result<()> {
+	let timed = TimedBackend::new(backend);
+	let result = submit_cached(&timed, year, day, part, options, answer).map(|_| ());
+	let timing = RunTiming {
+		submit_time: timed.submit_time(),
+		non_canonical_input: options.non_canonical_input,
+		..timing
+	};
+	log_run(year, day, part, answer, timing, &result);
+	result
+}
+
+/// Runs the solution, catching panics, and returns the answer and how long it took
+pub(crate) fn run_solution<SolOutput, SolFn>(
+	solution: SolFn,
+	input: &str,
+) -> Result<(String, Duration)>
+where
+	SolOutput: Display,
+	SolFn: FnOnce(&str) -> SolOutput,
+{
+	let (answer, solve_time) = run_timed(|| solution(input))?;
+	Ok((answer.to_string(), solve_time))
+}
+
+/// Runs the solution twice, the second time on another thread, and returns the answer and how long the first run took
+///
+/// Returns `Err(Error::NonDeterministicAnswer { .. })` if the runs give different answers
+pub(crate) fn run_solution_twice<SolOutput, SolFn>(
+	solution: &SolFn,
+	input: &str,
+) -> Result<(String, Duration)>
+where
+	SolOutput: Display,
+	SolFn: Fn(&str) -> SolOutput + Sync,
+{
+	let ((first, solve_time), (second, _)) = thread::scope(|scope| {
+		let second = scope.spawn(|| run_solution(solution, input));
+		let first = run_solution(solution, input);
+		let second = second.join().map_err(|err| Error::Panic(Some(err)))?;
+		Ok::<_, Error>((first?, second?))
+	})?;
+	match first == second {
+		true => Ok((first, solve_time)),
+		false => Err(Error::NonDeterministicAnswer { first, second }),
+	}
+}
+
+/// Runs `f`, catching panics, and returns its result and how long it took
+pub(crate) fn run_timed<T>(f: impl FnOnce() -> T) -> Result<(T, Duration)> {
+	let start = Instant::now();
+	let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+		.map_err(|err| Error::Panic(Some(err)))?;
+	Ok((result, start.elapsed()))
+}
+
+/// How long a run took, for the run log
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "local_cache"), allow(unused))]
+pub(crate) struct RunTiming {
+	pub(crate) solve_time: Duration,
+	/// Whether `solve_time` was spent on more than one part
+	pub(crate) shared_solve: bool,
+	/// Whether the answer was checked by solving twice (see `calculate_and_post_verified`)
+	pub(crate) verified: bool,
+	/// Whether the answer was solved from an input AoC didn't serve (see `InputSpec::is_canonical`)
+	pub(crate) non_canonical_input: bool,
+	pub(crate) submit_time: Option<Duration>,
+}
+
+impl RunTiming {
+	/// A single part solved once in `solve_time`, not yet submitted
+	pub(crate) fn solved(solve_time: Duration) -> Self {
+		Self {
+			solve_time,
+			shared_solve: false,
+			verified: false,
+			non_canonical_input: false,
+			submit_time: None,
+		}
+	}
+}
+
+/// Appends to the run log if `AOC_RUN_LOG` is set
+#[cfg_attr(not(feature = "local_cache"), allow(unused))]
+pub(crate) fn log_run(
+	year: i32,
+	day: i32,
+	part: i32,
+	answer: &str,
+	timing: RunTiming,
+	result: &Result<()>,
+) {
+	#[cfg(feature = "local_cache")]
+	if let Some(log_path) = std::env::var_os(run_log::RUN_LOG_VAR) {
+		let outcome = run_log::RunOutcome::from_result(result);
+		let mut record =
+			run_log::RunRecord::new(year, day, part, outcome, answer, timing.solve_time)
+				.with_submit_time(timing.submit_time);
+		record.shared_solve = timing.shared_solve;
+		record.verified = timing.verified;
+		record.non_canonical_input = timing.non_canonical_input;
+		// Ignore run log writing errors
+		let _ = run_log::append_run_record(log_path, &record);
+	}
+}
+
+/// The input and cache paths `aoc_magic!` uses for a day, under `dirs`
+#[doc(hidden)]
+pub fn magic_paths(dirs: impl AsRef<Path>, year: i32, day: i32) -> Result<(PathBuf, PathBuf)> {
+	let dirs = dirs.as_ref();
+	let mut input_path = create_data_dir(dirs.join("inputs"), year.to_string())?;
+	input_path.push(format!("{day}.txt"));
+
+	let mut cache_path = create_data_dir(dirs.join("cache"), year.to_string())?;
+	cache_path.push(format!("{day}.json"));
+
+	Ok((input_path, cache_path))
+}
+
+/// Magic macro to make AoC even easier
+///
+/// Usage: `aoc_magic!(<session cookie>, <year>:<day>:<part>, <solution function>)`
+///
+/// The session cookie can be left out, in which case it is read from `AOC_SESSION` (see `session_from_env`):
+/// `aoc_magic!(<year>:<day>:<part>, <solution function>)`
+///
+/// `both` can be given instead of `<part>` for a solution returning the answers to both parts as a tuple (see `calculate_and_post_both`):
+/// `aoc_magic!(<session cookie>, <year>:<day>:both, <solution function>)`
+///
+/// `today` (or `latest`) can be given instead of `<year>:<day>` to use the most recently unlocked puzzle (see `calendar::latest_puzzle`):
+/// `aoc_magic!(<session cookie>, today, <part>, <solution function>)`
+///
+/// The year, day and part can also be any expressions, e.g. loop variables, separated by commas:
+/// `aoc_magic!(<session cookie>, <year>, <day>, <part>, <solution function>)`
+///
+/// The `inputs` and `cache` directories are created in the current directory, or under the directory given with a trailing `dirs:`:
+/// `aoc_magic!(<session cookie>, <year>:<day>:<part>, <solution function>, dirs: "data/aoc")`
+///
+/// A trailing `global_cache` uses `default_data_dir` instead, which is the same for every project:
+/// `aoc_magic!(<session cookie>, <year>:<day>:<part>, <solution function>, global_cache)`
+#[macro_export]
+macro_rules! aoc_magic {
+	($year:literal : $day:literal : $part:tt, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!($year:$day:$part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	(today, $part:literal, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!(today, $part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	(latest, $part:literal, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!(today, $part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	($session:expr, $year:literal : $day:literal : $part:tt, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!($session, $year:$day:$part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	($session:expr, today, $part:literal, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!($session, today, $part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	($session:expr, latest, $part:literal, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!($session, today, $part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	($session:expr, $year:expr, $day:expr, $part:expr, $sol:expr, global_cache) => {
+		aoc_driver::aoc_magic!($session, $year, $day, $part, $sol, dirs: aoc_driver::default_data_dir())
+	};
+	($year:literal : $day:literal : both, $sol:expr $(, dirs: $dirs:expr)?) => {{
+		match aoc_driver::session_from_env() {
+			Err(e) => Err(e),
+			Ok(session) => aoc_driver::aoc_magic!(&session, $year:$day:both, $sol $(, dirs: $dirs)?),
+		}
+	}};
+	($session:expr, $year:literal : $day:literal : both, $sol:expr $(, dirs: $dirs:expr)?) => {{
+		match aoc_driver::calendar::check_unlocked($year, $day) {
+			Err(e) => Err(e),
+			Ok(()) => {
+				let dirs = "";
+				$(let dirs = $dirs;)?
+				let (input_path, cache_path) = aoc_driver::magic_paths(dirs, $year, $day).unwrap();
+
+				aoc_driver::calculate_and_post_both(
+					$session,
+					$year,
+					$day,
+					Some(&input_path),
+					Some(&cache_path),
+					$sol,
+				)
+			}
+		}
+	}};
+	(today, $part:literal, $sol:expr $(, dirs: $dirs:expr)?) => {{
+		match aoc_driver::session_from_env() {
+			Err(e) => Err(e),
+			Ok(session) => aoc_driver::aoc_magic!(&session, today, $part, $sol $(, dirs: $dirs)?),
+		}
+	}};
+	(latest, $part:literal, $sol:expr $(, dirs: $dirs:expr)?) => {
+		aoc_driver::aoc_magic!(today, $part, $sol $(, dirs: $dirs)?)
+	};
+	($year:literal : $day:literal : $part:literal, $sol:expr $(, dirs: $dirs:expr)?) => {{
+		match aoc_driver::session_from_env() {
+			Err(e) => Err(e),
+			Ok(session) => aoc_driver::aoc_magic!(&session, $year:$day:$part, $sol $(, dirs: $dirs)?),
+		}
+	}};
+	($session:expr, today, $part:literal, $sol:expr $(, dirs: $dirs:expr)?) => {{
+		match aoc_driver::calendar::latest_puzzle() {
+			Err(e) => Err(e),
+			Ok((year, day)) => aoc_driver::aoc_magic!($session, year, day, $part, $sol $(, dirs: $dirs)?),
+		}
+	}};
+	($session:expr, latest, $part:literal, $sol:expr $(, dirs: $dirs:expr)?) => {
+		aoc_driver::aoc_magic!($session, today, $part, $sol $(, dirs: $dirs)?)
+	};
+	($session:expr, $year:literal : $day:literal : $part:literal, $sol:expr $(, dirs: $dirs:expr)?) => {
+		aoc_driver::aoc_magic!($session, $year, $day, $part, $sol $(, dirs: $dirs)?)
+	};
+	($session:expr, $year:expr, $day:expr, $part:expr, $sol:expr $(, dirs: $dirs:expr)?) => {{
+		let (year, day, part): (i32, i32, i32) = ($year.into(), $day.into(), $part.into());
+		match aoc_driver::calendar::check_unlocked(year, day) {
+			Err(e) => Err(e),
+			Ok(()) => {
+				let dirs = "";
+				$(let dirs = $dirs;)?
+				let (input_path, cache_path) = aoc_driver::magic_paths(dirs, year, day).unwrap();
+
+				aoc_driver::calculate_and_post_with_label(
+					$session,
+					year,
+					day,
+					part,
+					Some(&input_path),
+					Some(&cache_path),
+					Some(stringify!($sol)),
+					$sol,
+				)
+			}
+		}
 	}};
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn zero_poll_interval() {
+		let err =
+			get_input_when_available("", 2015, 1, Some(Duration::ZERO), Duration::MAX, |_| {
+				panic!("nothing should be waited for")
+			})
+			.unwrap_err();
+		assert!(matches!(err, Error::InvalidArgument(_)));
+	}
+
+	#[test]
+	fn cached_input_empty() {
+		assert!(!validate_cached_input(""));
+		assert!(!validate_cached_input(" \n\n"));
+	}
+
+	#[test]
+	fn cached_input_login_page() {
+		let login_page = "<!DOCTYPE html>\n<html lang=\"en-us\">\n<head>\n<title>Advent of Code 2022</title>\n</head><body>\n<header><h1 class=\"title-global\"><a href=\"/\">Advent of Code</a></h1></header>\n<main>\n<p>To play, please identify yourself via one of these services:</p>\n<p><a href=\"/auth/github\">[GitHub]</a> <a href=\"/auth/google\">[Google]</a></p>\n</main>\n</body>\n</html>";
+		assert!(!validate_cached_input(login_page));
+		assert!(!validate_cached_input(
+			"Puzzle inputs differ by user.  Please log in to get your puzzle input.\n"
+		));
+	}
+
+	#[test]
+	fn cached_input_valid() {
+		assert!(validate_cached_input("1"));
+		assert!(validate_cached_input("<>^v\n>>v<"));
+	}
+
+	#[test]
+	fn preview_plain_answers() {
+		assert_eq!(render_answer_preview("42"), r#""42" (2 bytes, an integer)"#);
+		assert_eq!(render_answer_preview("-7"), r#""-7" (2 bytes, an integer)"#);
+		assert_eq!(render_answer_preview(""), r#""" (0 bytes, not an integer)"#);
+	}
+
+	#[test]
+	fn preview_escapes_control_characters() {
+		assert_eq!(
+			render_answer_preview("42\n"),
+			r#""42\n" (3 bytes, not an integer)"#
+		);
+		assert_eq!(
+			render_answer_preview("a\tb\r"),
+			r#""a\tb\r" (4 bytes, not an integer)"#
+		);
+		assert_eq!(
+			render_answer_preview("\0\u{1b}"),
+			r#""\u{0}\u{1b}" (2 bytes, not an integer)"#
+		);
+		assert_eq!(
+			render_answer_preview(r#"say "hi" \o/"#),
+			r#""say \"hi\" \\o/" (12 bytes, not an integer)"#
+		);
+	}
+
+	#[test]
+	fn preview_keeps_multi_byte_characters() {
+		assert_eq!(
+			render_answer_preview("héllo 🎄"),
+			"\"héllo 🎄\" (11 bytes, not an integer)"
+		);
+	}
+
+	#[test]
+	fn preview_truncates_after_max_chars() {
+		let exact = "1".repeat(PREVIEW_MAX_CHARS);
+		assert_eq!(
+			render_answer_preview(&exact),
+			format!("\"{exact}\" ({PREVIEW_MAX_CHARS} bytes, an integer)")
+		);
+
+		let long = "1".repeat(PREVIEW_MAX_CHARS + 1);
+		assert_eq!(
+			render_answer_preview(&long),
+			format!(
+				"\"{exact}\"… ({} bytes, an integer, first {PREVIEW_MAX_CHARS} characters shown)",
+				PREVIEW_MAX_CHARS + 1
+			)
+		);
+	}
+
+	#[test]
+	fn preview_truncates_on_char_boundaries() {
+		// Two bytes per character, so the cut is after byte 128 rather than 64
+		let long = "é".repeat(PREVIEW_MAX_CHARS + 1);
+		let shown = "é".repeat(PREVIEW_MAX_CHARS);
+		assert_eq!(
+			render_answer_preview(&long),
+			format!(
+				"\"{shown}\"… ({} bytes, not an integer, first {PREVIEW_MAX_CHARS} characters shown)",
+				long.len()
+			)
+		);
+
+		// An escape at the cut doesn't count as more than one character
+		let long = format!("{}\n\n", "1".repeat(PREVIEW_MAX_CHARS - 1));
+		assert!(render_answer_preview(&long)
+			.starts_with(&format!("\"{}\\n\"…", "1".repeat(PREVIEW_MAX_CHARS - 1))));
+	}
+
+	#[test]
+	fn non_integer_answer_shows_preview() {
+		assert_eq!(
+			Error::NonIntegerAnswer("4 2\n".to_owned()).to_string(),
+			r#"expected an integer answer, got "4 2\n" (4 bytes, not an integer)"#
+		);
+	}
+}
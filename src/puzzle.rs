@@ -0,0 +1,236 @@
+//! Puzzle descriptions from the `/{year}/day/{day}` page
+
+/// Returns the `<article class="day-desc">` elements of a puzzle page, including their tags, one per line
+///
+/// There is one article per part which has been unlocked
+pub fn extract_articles(page: &str) -> String {
+	const START: &str = "<article class=\"day-desc\">";
+	const END: &str = "</article>";
+
+	let mut articles = Vec::new();
+	let mut rest = page;
+	while let Some(start) = rest.find(START) {
+		let article = &rest[start..];
+		let Some(end) = article.find(END)
+		else {
+			break;
+		};
+		articles.push(&article[..end + END.len()]);
+		rest = &article[end + END.len()..];
+	}
+	articles.join("\n")
+}
+
+/// Whether the puzzle description includes part 2, which only appears once part 1 is solved
+pub fn has_part_two(html: &str) -> bool { html.contains("--- Part Two ---") }
+
+/// An example from a puzzle description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+	pub input: String,
+	/// The answer given for the example, if one could be found - the last of `candidate_answers`
+	pub answer: Option<String>,
+	/// Every emphasised code value in the description between this example and the next, in order
+	pub candidate_answers: Vec<String>,
+}
+
+/// Finds the examples in puzzle HTML (e.g. from `get_puzzle`)
+///
+/// Each `<pre><code>` block is taken as an example input, and every `<code><em>` after it
+/// (before the next block) as a candidate answer, the last being the likeliest. This is a heuristic: it can pick up
+/// blocks which aren't inputs, or the wrong emphasised value, so check the results before relying on them
+pub fn extract_examples(html: &str) -> Vec<Example> {
+	const BLOCK_START: &str = "<pre><code>";
+	const BLOCK_END: &str = "</code></pre>";
+	const ANSWER_START: &str = "<code><em>";
+	const ANSWER_END: &str = "</em></code>";
+
+	let mut examples = Vec::new();
+	let mut rest = html;
+	while let Some(start) = rest.find(BLOCK_START) {
+		let block = &rest[start + BLOCK_START.len()..];
+		let Some(len) = block.find(BLOCK_END)
+		else {
+			break;
+		};
+		let mut input = decode_entities(&strip_tags(&block[..len]));
+		if input.ends_with('\n') {
+			input.pop();
+		}
+
+		rest = &block[len + BLOCK_END.len()..];
+		let mut prose = &rest[..rest.find(BLOCK_START).unwrap_or(rest.len())];
+		let mut candidate_answers = Vec::new();
+		while let Some(start) = prose.find(ANSWER_START) {
+			let answer = &prose[start + ANSWER_START.len()..];
+			let Some(len) = answer.find(ANSWER_END)
+			else {
+				break;
+			};
+			candidate_answers.push(decode_entities(&strip_tags(&answer[..len])));
+			prose = &answer[len + ANSWER_END.len()..];
+		}
+
+		examples.push(Example {
+			input,
+			answer: candidate_answers.last().cloned(),
+			candidate_answers,
+		});
+	}
+	examples
+}
+
+/// Converts a whole puzzle page into Markdown for reading in a terminal, dropping everything but the descriptions
+///
+/// Also accepts the output of `get_puzzle`, which is just the descriptions. See `to_markdown`
+pub fn puzzle_to_text(page: &str) -> String { to_markdown(&extract_articles(page)) }
+
+/// Converts puzzle HTML (e.g. from `get_puzzle`) into Markdown
+///
+/// Handles the tags AoC uses: headings, paragraphs, emphasis, inline and block code, lists and links.
+/// Other tags are dropped, keeping their text
+pub fn to_markdown(html: &str) -> String {
+	let mut md = String::with_capacity(html.len());
+	let mut in_pre = false;
+	let mut in_code = false;
+	let mut links = Vec::new();
+
+	let mut rest = html;
+	while !rest.is_empty() {
+		let (text, tag) = match rest.find('<') {
+			Some(start) => {
+				let Some(len) = rest[start..].find('>')
+				else {
+					md.push_str(&decode_entities(rest));
+					break;
+				};
+				let tag = &rest[start + 1..start + len];
+				let text = &rest[..start];
+				rest = &rest[start + len + 1..];
+				(text, Some(tag))
+			}
+			None => (std::mem::take(&mut rest), None),
+		};
+
+		// Whitespace between block elements is formatting of the HTML, not content
+		if in_pre || !(text.trim().is_empty() && text.contains('\n')) {
+			md.push_str(&decode_entities(text));
+		}
+
+		let Some(tag) = tag
+		else {
+			continue;
+		};
+		let closing = tag.starts_with('/');
+		let name = tag
+			.trim_start_matches('/')
+			.split(|c: char| c.is_whitespace() || c == '/')
+			.next()
+			.unwrap_or_default()
+			.to_ascii_lowercase();
+		match (name.as_str(), closing) {
+			("h2", false) => md.push_str("## "),
+			("h2" | "p" | "ul", true) => md.push_str("\n\n"),
+			// Code blocks are kept verbatim, so emphasis in them is dropped rather than marked
+			("em", _) if !in_code && !in_pre => md.push('*'),
+			("code", _) if !in_pre => {
+				in_code = !closing;
+				md.push('`');
+			}
+			("pre", false) => {
+				in_pre = true;
+				md.push_str("```\n");
+			}
+			("pre", true) => {
+				in_pre = false;
+				if !md.ends_with('\n') {
+					md.push('\n');
+				}
+				md.push_str("```\n\n");
+			}
+			("li", false) => md.push_str("- "),
+			("li" | "br", _) => md.push('\n'),
+			("a", false) => {
+				links.push(attribute(tag, "href").unwrap_or_default().to_owned());
+				md.push('[');
+			}
+			("a", true) => {
+				let href = links.pop().unwrap_or_default();
+				md.push_str(&format!("]({})", decode_entities(&href)));
+			}
+			_ => (),
+		}
+	}
+
+	// Collapse runs of blank lines left by nested blocks
+	let mut collapsed = String::with_capacity(md.len());
+	let mut newlines = 0;
+	for c in md.trim().chars() {
+		newlines = if c == '\n' { newlines + 1 } else { 0 };
+		if newlines <= 2 {
+			collapsed.push(c);
+		}
+	}
+	collapsed.push('\n');
+	collapsed
+}
+
+pub(crate) fn strip_tags(html: &str) -> String {
+	let mut text = String::with_capacity(html.len());
+	let mut in_tag = false;
+	for c in html.chars() {
+		match c {
+			'<' => in_tag = true,
+			'>' if in_tag => in_tag = false,
+			c if !in_tag => text.push(c),
+			_ => (),
+		}
+	}
+	text
+}
+
+/// Value of a double quoted attribute of a tag, e.g. `href` of `a href="/2022/day/1"`
+fn attribute<'t>(tag: &'t str, name: &str) -> Option<&'t str> {
+	let start = tag.find(&format!("{name}=\""))? + name.len() + 2;
+	let len = tag[start..].find('"')?;
+	Some(&tag[start..start + len])
+}
+
+/// Decodes the named entities AoC uses, and numeric character references
+pub(crate) fn decode_entities(text: &str) -> String {
+	let mut decoded = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		decoded.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let entity = rest[1..].find(';').map(|len| &rest[1..len + 1]);
+		let c = entity.and_then(|entity| match entity {
+			"amp" => Some('&'),
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			"nbsp" => Some(' '),
+			_ => {
+				let code = entity.strip_prefix('#')?;
+				let code = match code.strip_prefix(['x', 'X']) {
+					Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+					None => code.parse().ok()?,
+				};
+				char::from_u32(code)
+			}
+		});
+		match (c, entity) {
+			(Some(c), Some(entity)) => {
+				decoded.push(c);
+				rest = &rest[entity.len() + 2..];
+			}
+			_ => {
+				decoded.push('&');
+				rest = &rest[1..];
+			}
+		}
+	}
+	decoded.push_str(rest);
+	decoded
+}
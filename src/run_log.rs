@@ -0,0 +1,181 @@
+//! Append-only log of what each run did, for reconstructing which code produced which answer
+//!
+//! Records are stored one JSON object per line. Answers are only ever stored as a hash.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+	fs::File,
+	io::{BufRead, BufReader, Write},
+	path::Path,
+	time::Duration,
+};
+
+/// Environment variable naming the run log file which `calculate_and_post` appends to
+pub const RUN_LOG_VAR: &str = "AOC_RUN_LOG";
+
+/// Environment variable holding an optional label (e.g. a git commit) stored with each record
+pub const RUN_LABEL_VAR: &str = "AOC_RUN_LABEL";
+
+/// Conventional location of the run log next to the caches created by `aoc_magic!`
+pub const DEFAULT_RUN_LOG_PATH: &str = "cache/run_log.ndjson";
+
+/// How a submission ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+	Correct,
+	Incorrect,
+	RateLimited,
+	Failed,
+}
+
+impl RunOutcome {
+	pub fn from_result(result: &Result<()>) -> Self {
+		match result {
+			Ok(()) => Self::Correct,
+			Err(e) if e.is_incorrect() => Self::Incorrect,
+			Err(Error::RateLimit(_)) => Self::RateLimited,
+			Err(_) => Self::Failed,
+		}
+	}
+}
+
+/// One line of the run log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+	pub timestamp: DateTime<Utc>,
+	pub year: i32,
+	pub day: i32,
+	pub part: i32,
+	pub outcome: RunOutcome,
+	/// See `hash_answer`
+	pub answer_hash: String,
+	pub solve_micros: u64,
+	/// Round trip time of the answer submission, if the answer was submitted rather than found in the cache
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub submit_micros: Option<u64>,
+	/// Whether the solve time was spent computing more than one part, e.g. by `calculate_and_post_both`
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub shared_solve: bool,
+	/// Whether the answer was checked by solving twice, e.g. by `calculate_and_post_verified`
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub verified: bool,
+	/// Whether the answer was solved from an input AoC didn't serve, e.g. an `InputSpec::Literal`
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub non_canonical_input: bool,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub label: Option<String>,
+}
+
+impl RunRecord {
+	/// Creates a record timestamped now, labelled from `AOC_RUN_LABEL` if it is set
+	pub fn new(
+		year: i32,
+		day: i32,
+		part: i32,
+		outcome: RunOutcome,
+		answer: &str,
+		solve_time: Duration,
+	) -> Self {
+		Self {
+			timestamp: Utc::now(),
+			year,
+			day,
+			part,
+			outcome,
+			answer_hash: hash_answer(answer),
+			solve_micros: as_micros(solve_time),
+			submit_micros: None,
+			shared_solve: false,
+			verified: false,
+			non_canonical_input: false,
+			label: std::env::var(RUN_LABEL_VAR).ok().filter(|l| !l.is_empty()),
+		}
+	}
+
+	/// Sets how long the answer submission took
+	pub fn with_submit_time(mut self, submit_time: Option<Duration>) -> Self {
+		self.submit_micros = submit_time.map(as_micros);
+		self
+	}
+
+	pub fn solve_time(&self) -> Duration { Duration::from_micros(self.solve_micros) }
+
+	pub fn submit_time(&self) -> Option<Duration> { self.submit_micros.map(Duration::from_micros) }
+}
+
+fn as_micros(duration: Duration) -> u64 { u64::try_from(duration.as_micros()).unwrap_or(u64::MAX) }
+
+/// Stable 64-bit FNV-1a hash of an answer, as lowercase hex
+pub fn hash_answer(answer: &str) -> String {
+	let hash = answer.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+		(hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+	});
+	format!("{hash:016x}")
+}
+
+/// Appends a record to the run log at `path`, creating it if needed
+///
+/// Each record is written with a single `write` to a file opened in append mode,
+/// so concurrent writers never interleave within a line
+pub fn append_run_record(path: impl AsRef<Path>, record: &RunRecord) -> Result<()> {
+	let mut line = serde_json::to_vec(record).expect("record is always serializable");
+	line.push(b'\n');
+	let mut file = File::options().create(true).append(true).open(path)?;
+	file.write_all(&line)?;
+	Ok(())
+}
+
+/// Reads every record from the run log at `path`
+///
+/// Returns `Err(Error::InvalidCache { .. })` if a line can't be parsed
+pub fn read_run_log(path: impl AsRef<Path>) -> Result<Vec<RunRecord>> {
+	let path = path.as_ref();
+	let reader = BufReader::new(File::open(path)?);
+	let mut records = Vec::new();
+	for (index, line) in reader.lines().enumerate() {
+		let line = line?;
+		if line.trim().is_empty() {
+			continue;
+		}
+		let record = serde_json::from_str(&line).map_err(|e| Error::InvalidCache {
+			path: path.to_owned(),
+			reason: format!("line {}: {}", index + 1, e),
+		})?;
+		records.push(record);
+	}
+	Ok(records)
+}
+
+/// Totals over a set of run records
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunLogSummary {
+	pub runs: usize,
+	pub correct: usize,
+	pub incorrect: usize,
+	pub rate_limited: usize,
+	pub failed: usize,
+	pub total_solve_time: Duration,
+	/// Time spent waiting on AoC to respond to submissions
+	pub total_submit_time: Duration,
+}
+
+/// Counts outcomes and adds up solve and submission times
+pub fn summarize_run_log(records: &[RunRecord]) -> RunLogSummary {
+	records
+		.iter()
+		.fold(RunLogSummary::default(), |mut summary, record| {
+			summary.runs += 1;
+			match record.outcome {
+				RunOutcome::Correct => summary.correct += 1,
+				RunOutcome::Incorrect => summary.incorrect += 1,
+				RunOutcome::RateLimited => summary.rate_limited += 1,
+				RunOutcome::Failed => summary.failed += 1,
+			}
+			summary.total_solve_time += record.solve_time();
+			summary.total_submit_time += record.submit_time().unwrap_or_default();
+			summary
+		})
+}
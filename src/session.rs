@@ -0,0 +1,326 @@
+//! Keeping track of whether the session cookie still works
+
+use crate::{
+	backend::{AocBackend, SubmissionOutcome, UreqBackend},
+	error::{Error, Result},
+	practice_mode,
+	puzzle::{decode_entities, strip_tags},
+	SESSION_VAR,
+};
+use chrono::{DateTime, Utc};
+use std::{
+	ffi::OsStr,
+	fmt,
+	io::Write,
+	ops::Deref,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex, PoisonError},
+	time::Duration,
+};
+
+#[cfg(feature = "zeroize")]
+pub(crate) type Secret = zeroize::Zeroizing<String>;
+#[cfg(not(feature = "zeroize"))]
+pub(crate) type Secret = String;
+
+/// A session cookie, which is never printed
+///
+/// With the `zeroize` feature the cookie is wiped from memory when this is dropped
+#[derive(Clone, PartialEq, Eq)]
+pub struct Session(Secret);
+
+impl Session {
+	pub fn new(session: impl Into<String>) -> Self { Self(Secret::from(session.into())) }
+
+	/// Reads the cookie from the file at `path`, e.g. `.session.txt`
+	///
+	/// Returns `Err(Error::SessionMissing)` if the file is empty, or `Err(Error::SessionInvalid)` if it isn't a hex cookie
+	pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+		#[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+		let mut contents = std::fs::read_to_string(path)?;
+		let session = Self::parse(&contents);
+		#[cfg(feature = "zeroize")]
+		zeroize::Zeroize::zeroize(&mut contents);
+		session
+	}
+
+	/// Reads the cookie from the environment variable `var`, e.g. `AOC_SESSION`
+	///
+	/// Returns `Err(Error::SessionMissing)` if it is unset or empty, or `Err(Error::SessionInvalid)` if it isn't a hex cookie
+	pub fn from_env(var: impl AsRef<OsStr>) -> Result<Self> {
+		#[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+		let mut value = std::env::var(var).unwrap_or_default();
+		let session = Self::parse(&value);
+		#[cfg(feature = "zeroize")]
+		zeroize::Zeroize::zeroize(&mut value);
+		session
+	}
+
+	/// Looks for the cookie in `AOC_SESSION`, then `.session.txt`, then `~/.config/aoc/session`
+	///
+	/// Returns `Err(Error::SessionMissing)` if none of them has one, unless in practice mode (see `practice_mode`)
+	pub fn discover() -> Result<Self> {
+		match Self::from_env(SESSION_VAR) {
+			Err(Error::SessionMissing) => (),
+			found => return found,
+		}
+		let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+		let config_file = home.map(|home| PathBuf::from(home).join(".config/aoc/session"));
+		for path in [Some(PathBuf::from(".session.txt")), config_file]
+			.into_iter()
+			.flatten()
+		{
+			match Self::from_file(path) {
+				Err(Error::IO(Some(e))) if e.kind() == std::io::ErrorKind::NotFound => (),
+				Err(Error::SessionMissing) => (),
+				found => return found,
+			}
+		}
+		match practice_mode() {
+			true => Ok(Self::new("")),
+			false => Err(Error::SessionMissing),
+		}
+	}
+
+	/// Trims `session`, checking it looks like a cookie
+	fn parse(session: &str) -> Result<Self> {
+		match session.trim() {
+			"" => Err(Error::SessionMissing),
+			trimmed if trimmed.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(Self::new(trimmed)),
+			_ => Err(Error::SessionInvalid),
+		}
+	}
+
+	/// The cookie itself
+	pub fn expose(&self) -> &str { &self.0 }
+}
+
+impl fmt::Debug for Session {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str("Session(<redacted>)") }
+}
+
+impl fmt::Display for Session {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str("<redacted session>") }
+}
+
+/// Lets a `&Session` be passed wherever a `&str` session is taken
+impl Deref for Session {
+	type Target = str;
+
+	fn deref(&self) -> &str { &self.0 }
+}
+
+impl AsRef<str> for Session {
+	fn as_ref(&self) -> &str { &self.0 }
+}
+
+impl From<String> for Session {
+	fn from(session: String) -> Self { Self::new(session) }
+}
+
+impl From<&str> for Session {
+	fn from(session: &str) -> Self { Self::new(session) }
+}
+
+/// The `Cookie` header value for `session`, wiped after use with the `zeroize` feature
+pub(crate) fn cookie_header(session: &str) -> Secret { Secret::from(format!("session={session}")) }
+
+/// Who a session belongs to, according to the AoC website
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+	/// The name shown on the site, e.g. `(anonymous user #123456)`
+	pub username: String,
+	/// Stars shown next to the name, if the page showed them
+	pub stars: Option<u32>,
+}
+
+/// Checks that `session` is logged in with one cheap request, e.g. before a run which fetches many inputs
+///
+/// Returns `Err(Error::SessionInvalid)` if it isn't
+pub fn check_session(session: &str) -> Result<SessionInfo> {
+	check_session_with(&UreqBackend::new(session))
+}
+
+fn check_session_with(backend: &UreqBackend) -> Result<SessionInfo> {
+	let page = backend.get_page("events")?;
+	parse_session_info(&page).ok_or(Error::SessionInvalid)
+}
+
+/// Reads the `<div class="user">` in the page header, which is only there when logged in
+fn parse_session_info(page: &str) -> Option<SessionInfo> {
+	const USER_START: &str = "<div class=\"user\">";
+	const STARS_START: &str = "<span class=\"star-count\">";
+
+	let user = &page[page.find(USER_START)? + USER_START.len()..];
+	let user = &user[..user.find("</div>")?];
+	let username = user[..user.find('<').unwrap_or(user.len())].trim();
+	if username.is_empty() {
+		return None;
+	}
+	let stars = user.find(STARS_START).and_then(|start| {
+		let stars = strip_tags(&user[start + STARS_START.len()..]);
+		stars.trim().trim_end_matches('*').parse().ok()
+	});
+	Some(SessionInfo {
+		username: decode_entities(username),
+		stars,
+	})
+}
+
+/// How long AoC session cookies last - `probably_expired` suspects older sessions
+pub const SESSION_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+type ExpiredHook = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Default)]
+struct MonitorState {
+	created: Option<DateTime<Utc>>,
+	last_ok: Option<DateTime<Utc>>,
+	expired: bool,
+	on_expired: Option<ExpiredHook>,
+}
+
+/// Records how requests made with a session went, so an expired session is reported once rather than as
+/// a confusing failure of every later request
+///
+/// Once a request has been rejected with `Error::SessionInvalid` and `check_session` confirms it,
+/// further requests fail with it straight away until `AocClient::reload_session` is called
+pub struct SessionMonitor {
+	state: Mutex<MonitorState>,
+}
+
+impl fmt::Debug for SessionMonitor {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let state = self.state();
+		f.debug_struct("SessionMonitor")
+			.field("last_ok", &state.last_ok)
+			.field("expired", &state.expired)
+			.finish_non_exhaustive()
+	}
+}
+
+impl SessionMonitor {
+	pub(crate) fn new() -> Self {
+		Self {
+			state: Mutex::new(MonitorState {
+				created: Some(Utc::now()),
+				..MonitorState::default()
+			}),
+		}
+	}
+
+	fn state(&self) -> std::sync::MutexGuard<'_, MonitorState> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	/// When a request was last accepted by the server
+	pub fn last_ok(&self) -> Option<DateTime<Utc>> { self.state().last_ok }
+
+	/// Whether the session has been rejected since it was last accepted,
+	/// or was last known to work longer ago than `SESSION_LIFETIME`
+	pub fn probably_expired(&self) -> bool {
+		let state = self.state();
+		let last_known_good = state.last_ok.or(state.created);
+		state.expired
+			|| last_known_good.is_some_and(|time| {
+				(Utc::now() - time)
+					.to_std()
+					.is_ok_and(|age| age > SESSION_LIFETIME)
+			})
+	}
+
+	/// Calls `hook` the first time the session is rejected, e.g. to tell the user to refresh `.session.txt`
+	///
+	/// It is called again only if a reloaded session is also rejected
+	pub fn on_expired(&self, hook: impl Fn() + Send + Sync + 'static) {
+		self.state().on_expired = Some(Arc::new(hook));
+	}
+
+	/// Forgets that the session was rejected, after it has been replaced
+	pub(crate) fn reset(&self) {
+		let mut state = self.state();
+		state.expired = false;
+		state.created = Some(Utc::now());
+	}
+
+	/// `Err(Error::SessionInvalid)` if the session is known to be rejected
+	fn check(&self) -> Result<()> {
+		match self.expired() {
+			true => Err(Error::SessionInvalid),
+			false => Ok(()),
+		}
+	}
+
+	fn expired(&self) -> bool { self.state().expired }
+
+	/// Notes the result of a request made with the session
+	fn record<T>(&self, result: Result<T>) -> Result<T> {
+		let mut state = self.state();
+		let hook = match &result {
+			Ok(_)
+			| Err(
+				Error::Incorrect
+				| Error::IncorrectWithHint(_)
+				| Error::RateLimit(_)
+				| Error::PuzzleLocked { .. },
+			) => {
+				state.last_ok = Some(Utc::now());
+				None
+			}
+			Err(Error::SessionInvalid) if !state.expired => {
+				state.expired = true;
+				state.on_expired.clone()
+			}
+			Err(_) => None,
+		};
+		// Called without the lock held, so the hook can use the monitor
+		drop(state);
+		if let Some(hook) = hook {
+			hook();
+		}
+		result
+	}
+}
+
+impl MonitoredBackend<'_, UreqBackend<'_>> {
+	/// `check_session`, recording the result
+	pub(crate) fn check_session(&self) -> Result<SessionInfo> {
+		self.monitor.check()?;
+		self.monitor.record(check_session_with(&self.backend))
+	}
+
+	/// Notes the result of a request in the monitor
+	///
+	/// The first time the session is rejected it is checked again with `check_session`, and only reported as expired
+	/// (calling the `on_expired` hook) if that is rejected too, as one page which looked logged out can be a fluke
+	fn record<T>(&self, result: Result<T>) -> Result<T> {
+		if matches!(result, Err(Error::SessionInvalid)) && !self.monitor.expired() {
+			let _ = self.monitor.record(check_session_with(&self.backend));
+			return result;
+		}
+		self.monitor.record(result)
+	}
+}
+
+/// Records every request made through `backend` in `monitor`
+pub(crate) struct MonitoredBackend<'m, B> {
+	pub(crate) backend: B,
+	pub(crate) monitor: &'m SessionMonitor,
+}
+
+impl AocBackend for MonitoredBackend<'_, UreqBackend<'_>> {
+	fn fetch_input(&self, year: i32, day: i32) -> Result<String> {
+		self.monitor.check()?;
+		self.record(self.backend.fetch_input(year, day))
+	}
+
+	fn fetch_input_to_writer(&self, year: i32, day: i32, writer: &mut dyn Write) -> Result<u64> {
+		self.monitor.check()?;
+		self.record(self.backend.fetch_input_to_writer(year, day, writer))
+	}
+
+	fn submit(&self, year: i32, day: i32, part: i32, answer: &str) -> Result<SubmissionOutcome> {
+		self.monitor.check()?;
+		self.record(self.backend.submit(year, day, part, answer))
+	}
+}
@@ -0,0 +1,100 @@
+//! Personal completion times from the `/{year}/leaderboard/self` page
+
+use crate::{backend::UreqBackend, duration, error::Result, puzzle::strip_tags};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Completion statistics for one part of a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartTime {
+	/// Time from unlock to completion, or `None` if AoC only shows `>24h`
+	pub time: Option<Duration>,
+	pub rank: Option<u32>,
+	pub score: Option<u32>,
+}
+
+/// Completion statistics for both parts of a day, `None` where the part isn't solved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DayTimes {
+	pub part1: Option<PartTime>,
+	pub part2: Option<PartTime>,
+}
+
+/// Personal completion statistics for a year, keyed by day
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SelfTimes {
+	pub days: BTreeMap<i32, DayTimes>,
+}
+
+/// Fetch and parse your personal completion times for `year`
+pub fn get_self_times(session: &str, year: impl Into<i32>) -> Result<SelfTimes> {
+	let page = UreqBackend::new(session).get_page(&format!("{}/leaderboard/self", year.into()))?;
+	Ok(parse_self_times(&page))
+}
+
+/// Parse the personal leaderboard page
+///
+/// Rows which don't look like `day time rank score time rank score` are ignored
+pub fn parse_self_times(page: &str) -> SelfTimes {
+	let text = strip_tags(page).replace("&gt;", ">");
+	let days = text
+		.lines()
+		.filter_map(|line| {
+			let columns = line.split_whitespace().collect::<Vec<_>>();
+			let [day, time1, rank1, score1, time2, rank2, score2] = columns[..]
+			else {
+				return None;
+			};
+			let times = DayTimes {
+				part1: parse_part([time1, rank1, score1])?,
+				part2: parse_part([time2, rank2, score2])?,
+			};
+			let day = day.parse().ok()?;
+			Some((day, times))
+		})
+		.collect();
+	SelfTimes { days }
+}
+
+/// One sentence describing how a part went, e.g. `part 2 solved 1h 42m after unlock; your rank 8124`
+pub fn describe_part(times: &SelfTimes, day: i32, part: i32) -> Option<String> {
+	let day_times = times.days.get(&day)?;
+	let part_time = match part {
+		1 => day_times.part1?,
+		2 => day_times.part2?,
+		_ => return None,
+	};
+	let time = part_time
+		.time
+		.map_or_else(|| String::from("more than 24h"), duration::format_short);
+	let mut description = format!("part {part} solved {time} after unlock");
+	if let Some(rank) = part_time.rank {
+		description.push_str(&format!("; your rank {rank}"));
+	}
+	Some(description)
+}
+
+/// `Some(None)` for an unsolved part, `None` if the columns are malformed
+fn parse_part([time, rank, score]: [&str; 3]) -> Option<Option<PartTime>> {
+	if time == "-" {
+		return Some(None);
+	}
+	let time = match time {
+		">24h" => None,
+		time => Some(parse_clock(time)?),
+	};
+	Some(Some(PartTime {
+		time,
+		rank: rank.parse().ok(),
+		score: score.parse().ok(),
+	}))
+}
+
+/// Parses `HH:MM:SS`
+fn parse_clock(time: &str) -> Option<Duration> {
+	let mut parts = time.split(':').map(|p| p.parse::<u64>().ok());
+	let (h, m, s) = (parts.next()??, parts.next()??, parts.next()??);
+	parts
+		.next()
+		.is_none()
+		.then(|| Duration::from_secs(h * 3600 + m * 60 + s))
+}
@@ -0,0 +1,263 @@
+//! Where inputs and caches are persisted
+//!
+//! Everything goes through `LocalStorage` unless another `Storage` is installed with `set_storage`,
+//! e.g. for network or FUSE filesystems which need special handling
+
+use crate::{error::Result, sibling_path};
+use std::{
+	fs::File,
+	io::Write,
+	path::{Path, PathBuf},
+	sync::{Arc, PoisonError, RwLock},
+	thread,
+	time::{Duration, SystemTime},
+};
+
+/// Produces the contents of a file, for `Storage::write_atomic_with`
+pub type WriteFn<'a> = Box<dyn FnOnce(&mut dyn Write) -> Result<()> + 'a>;
+
+/// Persistence of inputs and caches
+pub trait Storage: Send + Sync {
+	/// Reads the whole file at `path`
+	///
+	/// A missing file is an `Error::IO` of kind `NotFound`
+	fn read(&self, path: &Path) -> Result<Vec<u8>>;
+
+	/// Replaces the file at `path` with `contents`, never leaving it partially written
+	fn write_atomic(&self, path: &Path, contents: &[u8]) -> Result<()>;
+
+	/// Like `write_atomic`, but `write` produces the contents
+	///
+	/// By default the contents are buffered in memory and passed to `write_atomic`.
+	/// If `write` fails `path` must be left as it was
+	fn write_atomic_with(&self, path: &Path, write: WriteFn<'_>) -> Result<()> {
+		let mut contents = Vec::new();
+		write(&mut contents)?;
+		self.write_atomic(path, &contents)
+	}
+
+	/// Takes an exclusive lock on `path`, waiting for other holders to release it
+	fn lock(&self, path: &Path) -> Result<()>;
+
+	/// Releases a lock taken with `lock`
+	fn unlock(&self, path: &Path);
+
+	/// Paths of the entries in the directory `dir`
+	fn list(&self, dir: &Path) -> Result<Vec<PathBuf>>;
+}
+
+/// How long `LocalStorage::lock` waits before assuming the lock was left behind by a process which died
+///
+/// Where it can tell (on systems with `/proc`), a lock whose holder has exited is taken over straight away
+pub const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const VERIFY_ATTEMPTS: u32 = 3;
+
+/// Files on the local filesystem
+///
+/// Writes go to a temporary file which is renamed into place. On filesystems where that doesn't work
+/// (see `LocalStorage::probe`), the file is written in place and read back until it matches.
+/// Locks are `{file name}.lock` files next to the locked file, holding the ID of the process which took them.
+/// Waiting for one is reported through `on_lock_wait`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalStorage {
+	atomic_rename: bool,
+}
+
+impl Default for LocalStorage {
+	fn default() -> Self {
+		Self {
+			atomic_rename: true,
+		}
+	}
+}
+
+impl LocalStorage {
+	/// Checks whether renaming over an existing file works in `dir`, and writes accordingly
+	pub fn probe(dir: impl AsRef<Path>) -> Self {
+		Self {
+			atomic_rename: supports_atomic_rename(dir.as_ref()),
+		}
+	}
+
+	/// Writes files in place and reads them back, as `probe` chooses where renaming doesn't work,
+	/// e.g. for a filesystem where it only sometimes misbehaves
+	pub fn in_place() -> Self {
+		Self {
+			atomic_rename: false,
+		}
+	}
+
+	/// Whether writes rename a temporary file into place
+	pub fn atomic_rename(&self) -> bool { self.atomic_rename }
+
+	/// Writes `contents` in place, reading them back to check they arrived intact
+	fn write_verified(&self, path: &Path, contents: &[u8]) -> Result<()> {
+		let mut attempt = 1;
+		loop {
+			let written = std::fs::write(path, contents).and_then(|()| std::fs::read(path));
+			match written {
+				Ok(read_back) if read_back == contents => return Ok(()),
+				Ok(_) if attempt < VERIFY_ATTEMPTS => (),
+				Ok(_) => {
+					return Err(std::io::Error::other(format!(
+						"{} did not read back as written",
+						path.display()
+					))
+					.into())
+				}
+				Err(_) if attempt < VERIFY_ATTEMPTS => (),
+				Err(e) => return Err(e.into()),
+			}
+			attempt += 1;
+		}
+	}
+}
+
+impl Storage for LocalStorage {
+	fn read(&self, path: &Path) -> Result<Vec<u8>> { Ok(std::fs::read(path)?) }
+
+	fn write_atomic(&self, path: &Path, contents: &[u8]) -> Result<()> {
+		if !self.atomic_rename {
+			return self.write_verified(path, contents);
+		}
+		self.write_atomic_with(path, Box::new(|tmp| Ok(tmp.write_all(contents)?)))
+	}
+
+	fn write_atomic_with(&self, path: &Path, write: WriteFn<'_>) -> Result<()> {
+		if !self.atomic_rename {
+			let mut contents = Vec::new();
+			write(&mut contents)?;
+			return self.write_verified(path, &contents);
+		}
+		let tmp_path = sibling_path(path, ".tmp");
+		let write = || -> Result<()> {
+			let mut tmp = File::create(&tmp_path)?;
+			write(&mut tmp)?;
+			tmp.sync_all()?;
+			std::fs::rename(&tmp_path, path)?;
+			Ok(())
+		};
+		write().inspect_err(|_| {
+			let _ = std::fs::remove_file(&tmp_path);
+		})
+	}
+
+	fn lock(&self, path: &Path) -> Result<()> {
+		let lock_path = sibling_path(path, ".lock");
+		let mut waiting = false;
+		loop {
+			match File::options()
+				.write(true)
+				.create_new(true)
+				.open(&lock_path)
+			{
+				Ok(mut lock) => {
+					let _ = write!(lock, "{}", std::process::id());
+					return Ok(());
+				}
+				Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+					let holder = std::fs::read_to_string(&lock_path)
+						.ok()
+						.and_then(|pid| pid.trim().parse::<u32>().ok());
+					let age = std::fs::metadata(&lock_path)
+						.and_then(|metadata| metadata.modified())
+						.ok()
+						.and_then(|modified| SystemTime::now().duration_since(modified).ok());
+					if holder.is_some_and(|pid| !process_running(pid))
+						|| age.is_some_and(|age| age >= STALE_LOCK_AGE)
+					{
+						let _ = std::fs::remove_file(&lock_path);
+						continue;
+					}
+					if !waiting {
+						waiting = true;
+						let hook = ON_LOCK_WAIT
+							.read()
+							.unwrap_or_else(PoisonError::into_inner)
+							.clone();
+						if let Some(hook) = hook {
+							hook(&lock_path, holder);
+						}
+					}
+					thread::sleep(LOCK_POLL_INTERVAL);
+				}
+				Err(e) => return Err(e.into()),
+			}
+		}
+	}
+
+	fn unlock(&self, path: &Path) { let _ = std::fs::remove_file(sibling_path(path, ".lock")); }
+
+	fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+		std::fs::read_dir(dir)?
+			.map(|entry| Ok(entry?.path()))
+			.collect()
+	}
+}
+
+/// Whether the process `pid` is still running, assuming it is where that can't be checked
+fn process_running(pid: u32) -> bool {
+	let proc = Path::new("/proc");
+	!proc.join("self").exists() || proc.join(pid.to_string()).exists()
+}
+
+/// Whether a file can be renamed over an existing one in `dir`, leaving the new contents in place
+pub fn supports_atomic_rename(dir: &Path) -> bool {
+	let target = dir.join(".aoc_driver_probe");
+	let tmp = sibling_path(&target, ".tmp");
+	let works = std::fs::write(&target, b"old").is_ok()
+		&& std::fs::write(&tmp, b"new").is_ok()
+		&& std::fs::rename(&tmp, &target).is_ok()
+		&& std::fs::read(&target).is_ok_and(|contents| contents == b"new");
+	let _ = std::fs::remove_file(&tmp);
+	let _ = std::fs::remove_file(&target);
+	works
+}
+
+static STORAGE: RwLock<Option<Arc<dyn Storage>>> = RwLock::new(None);
+
+type LockWaitHook = Arc<dyn Fn(&Path, Option<u32>) + Send + Sync>;
+
+static ON_LOCK_WAIT: RwLock<Option<LockWaitHook>> = RwLock::new(None);
+
+/// Calls `hook` with the lock file and the ID of the process holding it, if known,
+/// whenever `LocalStorage::lock` starts waiting for a lock, e.g. to say why a submission is stalled
+pub fn on_lock_wait(hook: impl Fn(&Path, Option<u32>) + Send + Sync + 'static) {
+	*ON_LOCK_WAIT.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(hook));
+}
+
+/// Persists all inputs and caches through `storage` instead of `LocalStorage`
+pub fn set_storage(storage: impl Storage + 'static) {
+	*STORAGE.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(storage));
+}
+
+/// The storage set with `set_storage`, or `LocalStorage`
+pub(crate) fn storage() -> Arc<dyn Storage> {
+	STORAGE
+		.read()
+		.unwrap_or_else(PoisonError::into_inner)
+		.clone()
+		.unwrap_or_else(|| Arc::new(LocalStorage::default()))
+}
+
+/// Holds the lock on a path until dropped
+#[cfg(feature = "local_cache")]
+pub(crate) struct LockGuard<'a> {
+	storage: &'a dyn Storage,
+	path: &'a Path,
+}
+
+#[cfg(feature = "local_cache")]
+impl<'a> LockGuard<'a> {
+	pub(crate) fn lock(storage: &'a dyn Storage, path: &'a Path) -> Result<Self> {
+		storage.lock(path)?;
+		Ok(Self { storage, path })
+	}
+}
+
+#[cfg(feature = "local_cache")]
+impl Drop for LockGuard<'_> {
+	fn drop(&mut self) { self.storage.unlock(self.path); }
+}
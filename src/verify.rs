@@ -0,0 +1,166 @@
+//! Offline regression checks of solutions against stored inputs and known answers
+//!
+//! A manifest is a JSON array of entries like
+//! `{ "year": 2022, "day": 1, "part": 1, "solver": "day1_part1", "input_file": "inputs/2022/1.txt", "expected": "24000" }`.
+//! `input_file` is relative to the manifest's directory. Nothing is downloaded or submitted.
+
+use crate::{
+	error::{Error, Result},
+	run_solution,
+};
+use serde::Deserialize;
+use std::{
+	fmt,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+/// A named solution function
+pub type Solver<'a> = (&'a str, fn(&str) -> String);
+
+/// One line of a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ManifestEntry {
+	pub year: i32,
+	pub day: i32,
+	pub part: i32,
+	/// Name of the solver in the table given to `verify_all`
+	pub solver: String,
+	pub input_file: PathBuf,
+	pub expected: String,
+}
+
+/// How a single manifest entry went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyOutcome {
+	Passed,
+	WrongAnswer {
+		expected: String,
+		actual: String,
+	},
+	/// The solver couldn't be run, e.g. it wasn't in the table, the input couldn't be read or it panicked
+	Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResult {
+	pub entry: ManifestEntry,
+	pub outcome: VerifyOutcome,
+	pub solve_time: Duration,
+}
+
+/// Results of `verify_all`, in manifest order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+	pub results: Vec<VerifyResult>,
+}
+
+impl BatchReport {
+	/// Whether every entry passed
+	pub fn all_passed(&self) -> bool { self.failures().next().is_none() }
+
+	pub fn failures(&self) -> impl Iterator<Item = &VerifyResult> {
+		self.results
+			.iter()
+			.filter(|r| r.outcome != VerifyOutcome::Passed)
+	}
+}
+
+/// Lists each failure, with a line by line diff for wrong answers, followed by a total
+impl fmt::Display for BatchReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut failed = 0;
+		for result in self.failures() {
+			failed += 1;
+			let ManifestEntry {
+				year,
+				day,
+				part,
+				solver,
+				..
+			} = &result.entry;
+			write!(f, "{year} day {day} part {part} ({solver}): ")?;
+			match &result.outcome {
+				VerifyOutcome::Passed => unreachable!("only failures are listed"),
+				VerifyOutcome::WrongAnswer { expected, actual } => {
+					writeln!(f, "wrong answer")?;
+					for line in expected.lines() {
+						writeln!(f, "  -{line}")?;
+					}
+					for line in actual.lines() {
+						writeln!(f, "  +{line}")?;
+					}
+				}
+				VerifyOutcome::Failed(reason) => writeln!(f, "{reason}")?,
+			}
+		}
+		write!(
+			f,
+			"{} of {} passed",
+			self.results.len() - failed,
+			self.results.len()
+		)
+	}
+}
+
+/// Runs every entry of the manifest at `manifest` with the matching solver from `solvers`
+///
+/// Returns `Err` only if the manifest itself can't be read, or `Err(Error::InvalidArgument(..))` if it isn't valid;
+/// problems with individual entries are in the report.
+/// No global state is touched, so this can be called from tests running in parallel
+pub fn verify_all(manifest: impl AsRef<Path>, solvers: &[Solver]) -> Result<BatchReport> {
+	let manifest = manifest.as_ref();
+	let entries: Vec<ManifestEntry> = serde_json::from_str(&std::fs::read_to_string(manifest)?)
+		.map_err(|e| {
+			Error::InvalidArgument(format!("invalid manifest {}: {e}", manifest.display()))
+		})?;
+	let base = manifest.parent().unwrap_or(Path::new(""));
+
+	let results = entries
+		.into_iter()
+		.map(|entry| {
+			let (outcome, solve_time) = verify_entry(&entry, base, solvers);
+			VerifyResult {
+				entry,
+				outcome,
+				solve_time,
+			}
+		})
+		.collect();
+	Ok(BatchReport { results })
+}
+
+fn verify_entry(
+	entry: &ManifestEntry,
+	base: &Path,
+	solvers: &[Solver],
+) -> (VerifyOutcome, Duration) {
+	let Some((_, solver)) = solvers.iter().find(|(name, _)| *name == entry.solver)
+	else {
+		return (
+			VerifyOutcome::Failed(format!("no solver named {:?}", entry.solver)),
+			Duration::ZERO,
+		);
+	};
+	let input_path = base.join(&entry.input_file);
+	let input = match std::fs::read_to_string(&input_path) {
+		Ok(input) => input,
+		Err(e) => {
+			return (
+				VerifyOutcome::Failed(format!("could not read {}: {e}", input_path.display())),
+				Duration::ZERO,
+			)
+		}
+	};
+	match run_solution(solver, &input) {
+		Ok((actual, solve_time)) if actual == entry.expected => (VerifyOutcome::Passed, solve_time),
+		Ok((actual, solve_time)) => (
+			VerifyOutcome::WrongAnswer {
+				expected: entry.expected.clone(),
+				actual,
+			},
+			solve_time,
+		),
+		Err(e) => (VerifyOutcome::Failed(e.to_string()), Duration::ZERO),
+	}
+}
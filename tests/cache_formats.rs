@@ -0,0 +1,108 @@
+//! Caches written by every earlier format version must still be readable
+//!
+//! `tests/fixtures/cache/v{N}/` holds caches in format version N. Add one whenever `FORMAT_VERSION` is bumped
+
+#![cfg(feature = "local_cache")]
+
+use aoc_driver::{
+	backend::{AocBackend, SubmissionOutcome},
+	cache::{DayCache, FORMAT_VERSION},
+	error::Result,
+	*,
+};
+use std::{cell::Cell, path::PathBuf};
+
+mod common;
+
+use common::temp_dir;
+
+/// Fails the test if anything is submitted, since every answer should come from the cache
+#[derive(Default)]
+struct NoServer {
+	submissions: Cell<u32>,
+}
+
+impl AocBackend for NoServer {
+	fn fetch_input(&self, _year: i32, _day: i32) -> Result<String> { unreachable!() }
+
+	fn submit(
+		&self,
+		_year: i32,
+		_day: i32,
+		_part: i32,
+		_answer: &str,
+	) -> Result<SubmissionOutcome> {
+		self.submissions.set(self.submissions.get() + 1);
+		Ok(SubmissionOutcome::Correct)
+	}
+}
+
+fn version_dirs() -> Vec<(u32, PathBuf)> {
+	let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cache");
+	(1..=FORMAT_VERSION)
+		.map(|version| (version, root.join(format!("v{version}"))))
+		.collect()
+}
+
+fn cache_files() -> Vec<(u32, PathBuf)> {
+	let mut files = Vec::new();
+	for (version, dir) in version_dirs() {
+		let entries = std::fs::read_dir(&dir).unwrap_or_else(|e| {
+			panic!(
+				"no fixtures for format version {version} in {}: {e}",
+				dir.display()
+			)
+		});
+		for entry in entries {
+			let path = entry.unwrap().path();
+			if path
+				.extension()
+				.is_some_and(|extension| extension == "json")
+			{
+				files.push((version, path));
+			}
+		}
+	}
+	files
+}
+
+#[test]
+fn every_format_version_parses() {
+	let files = cache_files();
+	assert!(files.len() >= FORMAT_VERSION as usize);
+	for (version, path) in files {
+		let contents = std::fs::read_to_string(&path).unwrap();
+		let cache = serde_json::from_str::<DayCache>(&contents)
+			.unwrap_or_else(|e| panic!("v{version} {}: {e}", path.display()));
+		assert_eq!(cache.parts(), [1, 2], "v{version}");
+		assert_eq!(cache.correct_answer(1), Some("514579"), "v{version}");
+		assert_eq!(cache.correct_answer(2), None, "v{version}");
+		assert!(cache.attempts(1) >= 2, "v{version}");
+	}
+}
+
+#[test]
+fn every_format_version_answers_submissions() {
+	for (version, path) in cache_files() {
+		let cache_path = temp_dir(&format!("cache_v{version}")).join("1.json");
+		std::fs::copy(&path, &cache_path).unwrap();
+		let backend = NoServer::default();
+		let submit = |part, answer: &str| {
+			calculate_and_post_with_backend(
+				&backend,
+				2020,
+				1,
+				part,
+				InputSpec::Literal(String::new()),
+				Some(&cache_path),
+				None,
+				|_| answer.to_owned(),
+			)
+		};
+
+		assert!(submit(1, "514579").is_ok(), "v{version}");
+		assert!(submit(1, "12").unwrap_err().is_incorrect(), "v{version}");
+		assert!(submit(2, "7").unwrap_err().is_incorrect(), "v{version}");
+		assert_eq!(backend.submissions.get(), 0, "v{version}");
+	}
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "local_cache")]
+
+use aoc_driver::{
+	backend::{AocBackend, SubmissionOutcome},
+	error::Result,
+	storage::{on_lock_wait, LocalStorage, Storage},
+	*,
+};
+use std::{
+	cell::Cell,
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	thread,
+	time::Duration,
+};
+
+mod common;
+
+use common::temp_dir;
+
+/// Accepts every answer, counting the submissions
+#[derive(Default)]
+struct CountingBackend {
+	submissions: Cell<u32>,
+}
+
+impl AocBackend for CountingBackend {
+	fn fetch_input(&self, _year: i32, _day: i32) -> Result<String> { Ok("1\n".to_owned()) }
+
+	fn submit(
+		&self,
+		_year: i32,
+		_day: i32,
+		_part: i32,
+		_answer: &str,
+	) -> Result<SubmissionOutcome> {
+		self.submissions.set(self.submissions.get() + 1);
+		Ok(SubmissionOutcome::Correct)
+	}
+}
+
+fn submit(backend: &CountingBackend, cache_path: Option<PathBuf>) -> Result<()> {
+	calculate_and_post_with_backend(
+		backend,
+		2022,
+		1,
+		1,
+		InputSpec::Default,
+		cache_path,
+		None,
+		|_| 1,
+	)
+}
+
+#[test]
+fn missing_cache_dir_still_submits() {
+	let backend = CountingBackend::default();
+	let missing = temp_dir("missing").join("missing/2022/1.json");
+	assert!(submit(&backend, Some(missing)).is_ok());
+	assert_eq!(backend.submissions.get(), 1);
+}
+
+#[test]
+fn lock_of_exited_process_is_taken_over() {
+	let backend = CountingBackend::default();
+	let cache_path = temp_dir("stale_lock").join("1.json");
+	std::fs::write(cache_path.with_extension("json.lock"), u32::MAX.to_string()).unwrap();
+	assert!(submit(&backend, Some(cache_path.clone())).is_ok());
+	assert_eq!(backend.submissions.get(), 1);
+	assert!(!cache_path.with_extension("json.lock").exists());
+}
+
+#[test]
+fn lock_wait_is_reported() {
+	let waits = Arc::new(Mutex::new(Vec::new()));
+	on_lock_wait({
+		let waits = waits.clone();
+		move |lock_path, holder| waits.lock().unwrap().push((lock_path.to_owned(), holder))
+	});
+	let path = temp_dir("lock_wait").join("1.json");
+	let lock_path = path.with_extension("json.lock");
+	std::fs::write(&lock_path, std::process::id().to_string()).unwrap();
+	let release = thread::spawn({
+		let lock_path = lock_path.clone();
+		move || {
+			thread::sleep(Duration::from_millis(200));
+			std::fs::remove_file(lock_path).unwrap();
+		}
+	});
+
+	let storage = LocalStorage::default();
+	storage.lock(&path).unwrap();
+	release.join().unwrap();
+	storage.unlock(&path);
+	assert_eq!(
+		*waits.lock().unwrap(),
+		[(lock_path, Some(std::process::id()))]
+	);
+}
@@ -0,0 +1,72 @@
+//! Errors from the public functions name the puzzle part they happened for
+
+use aoc_driver::{
+	backend::{AocBackend, SubmissionOutcome},
+	error::{Error, Result},
+	*,
+};
+
+/// Refuses every request as if the session had expired
+struct LoggedOut;
+
+impl AocBackend for LoggedOut {
+	fn fetch_input(&self, _year: i32, _day: i32) -> Result<String> { Err(Error::SessionInvalid) }
+
+	fn submit(
+		&self,
+		_year: i32,
+		_day: i32,
+		_part: i32,
+		_answer: &str,
+	) -> Result<SubmissionOutcome> {
+		Err(Error::SessionInvalid)
+	}
+}
+
+fn both(year: i32, solution: impl FnOnce(&str) -> (i32, i32)) -> Error {
+	calculate_and_post_both_with_backend(
+		&LoggedOut,
+		year,
+		1,
+		InputSpec::Default,
+		None::<&str>,
+		solution,
+	)
+	.unwrap_err()
+}
+
+#[test]
+fn both_input_failure_names_part_1() {
+	let err = both(2022, |_| (1, 2));
+	assert_eq!(err.challenge(), Some((2022, 1, 1)));
+	assert!(matches!(err.inner(), Error::SessionInvalid));
+}
+
+#[test]
+fn both_locked_puzzle_names_part_1() {
+	let err = both(2099, |_| (1, 2));
+	assert_eq!(err.challenge(), Some((2099, 1, 1)));
+	assert!(matches!(err.inner(), Error::PuzzleNotYetAvailable { .. }));
+}
+
+#[test]
+fn both_panic_names_part_1() {
+	let err = calculate_and_post_both_with_backend(
+		&LoggedOut,
+		2022,
+		1,
+		InputSpec::Literal("1\n".to_owned()),
+		None::<&str>,
+		|_| -> (i32, i32) { panic!("unsolved") },
+	)
+	.unwrap_err();
+	assert_eq!(err.challenge(), Some((2022, 1, 1)));
+	assert!(matches!(err.inner(), Error::Panic(_)));
+}
+
+#[test]
+fn non_integer_answer_names_part() {
+	let err = post_integer_answer("abc123", 2022, 1, 2, None::<&str>, "4 2").unwrap_err();
+	assert_eq!(err.challenge(), Some((2022, 1, 2)));
+	assert!(matches!(err.inner(), Error::NonIntegerAnswer(answer) if answer == "4 2"));
+}
@@ -0,0 +1,164 @@
+//! A tiny HTTP server on a local port standing in for adventofcode.com, and other helpers shared between tests
+
+#![allow(dead_code)]
+
+use std::{
+	collections::HashMap,
+	io::{BufRead, BufReader, Read, Write},
+	net::{TcpListener, TcpStream},
+	path::PathBuf,
+	sync::{Arc, Mutex},
+	thread,
+};
+
+/// A request the mock server received
+#[derive(Debug, Clone)]
+pub struct Request {
+	pub method: String,
+	pub path: String,
+	/// Header names are lowercase
+	pub headers: HashMap<String, String>,
+	pub body: String,
+}
+
+impl Request {
+	/// The value of `key` in the url encoded form body
+	pub fn form(&self, key: &str) -> Option<String> {
+		self.body
+			.split('&')
+			.filter_map(|pair| pair.split_once('='))
+			.find(|(k, _)| *k == key)
+			.map(|(_, v)| v.replace('+', " "))
+	}
+}
+
+/// What the mock server answers with
+pub struct Reply {
+	pub status: u16,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl Reply {
+	pub fn ok(body: impl Into<Vec<u8>>) -> Self { Self::status(200, body) }
+
+	pub fn status(status: u16, body: impl Into<Vec<u8>>) -> Self {
+		Self {
+			status,
+			headers: Vec::new(),
+			body: body.into(),
+		}
+	}
+
+	pub fn header(mut self, name: &str, value: &str) -> Self {
+		self.headers.push((name.to_owned(), value.to_owned()));
+		self
+	}
+}
+
+type Handler = Arc<dyn Fn(&Request) -> Reply + Send + Sync>;
+
+/// Serves each request with `handler` on a background thread until the test process exits
+pub struct MockServer {
+	url: String,
+	requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+	pub fn start(handler: impl Fn(&Request) -> Reply + Send + Sync + 'static) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let handler: Handler = Arc::new(handler);
+		let log = requests.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming().flatten() {
+				let (handler, log) = (handler.clone(), log.clone());
+				thread::spawn(move || serve(stream, &*handler, &log));
+			}
+		});
+		Self { url, requests }
+	}
+
+	/// Base URL to point the crate at, e.g. `http://127.0.0.1:12345`
+	pub fn url(&self) -> &str { &self.url }
+
+	/// Every request received so far
+	pub fn requests(&self) -> Vec<Request> { self.requests.lock().unwrap().clone() }
+
+	/// How many requests were sent to `path`
+	pub fn hits(&self, path: &str) -> usize {
+		self.requests()
+			.iter()
+			.filter(|request| request.path == path)
+			.count()
+	}
+}
+
+fn serve(stream: TcpStream, handler: &dyn Fn(&Request) -> Reply, log: &Mutex<Vec<Request>>) {
+	let mut reader = BufReader::new(stream);
+	let mut line = String::new();
+	if reader.read_line(&mut line).unwrap_or(0) == 0 {
+		return;
+	}
+	let mut parts = line.split_whitespace();
+	let method = parts.next().unwrap_or_default().to_owned();
+	let path = parts.next().unwrap_or_default().to_owned();
+
+	let mut headers = HashMap::new();
+	loop {
+		let mut line = String::new();
+		if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+			break;
+		}
+		if let Some((name, value)) = line.split_once(':') {
+			headers.insert(name.trim().to_lowercase(), value.trim().to_owned());
+		}
+	}
+	let length = headers
+		.get("content-length")
+		.and_then(|length| length.parse().ok())
+		.unwrap_or(0);
+	let mut body = vec![0; length];
+	let _ = reader.read_exact(&mut body);
+
+	let request = Request {
+		method,
+		path,
+		headers,
+		body: String::from_utf8_lossy(&body).into_owned(),
+	};
+	let reply = handler(&request);
+	log.lock().unwrap().push(request);
+
+	let mut stream = reader.into_inner();
+	let mut head = format!(
+		"HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+		reply.status,
+		reply.body.len()
+	);
+	for (name, value) in &reply.headers {
+		head.push_str(&format!("{name}: {value}\r\n"));
+	}
+	head.push_str("\r\n");
+	let _ = stream.write_all(head.as_bytes());
+	let _ = stream.write_all(&reply.body);
+}
+
+/// An empty directory unique to this test process
+pub fn temp_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("aoc_driver_{name}_{}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	dir
+}
+
+/// Wraps `text` in a page like the one AoC answers submissions with
+pub fn answer_page(text: &str) -> String {
+	format!("<!DOCTYPE html>\n<html><body><main>\n<article><p>{text}</p></article>\n</main></body></html>\n")
+}
+
+pub const CORRECT: &str =
+	"That's the right answer!  You are one gold star closer to saving your vacation.";
+pub const TOO_HIGH: &str = "That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data.";
+pub const RATE_LIMITED: &str = "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 30s left to wait.";
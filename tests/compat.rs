@@ -0,0 +1,40 @@
+//! The legacy bool-returning functions against a mock AoC
+
+#![allow(deprecated)]
+
+use aoc_driver::{compat::*, *};
+use std::time::Duration;
+
+mod common;
+
+use common::{answer_page, temp_dir, MockServer, Reply, CORRECT, RATE_LIMITED, TOO_HIGH};
+
+#[test]
+fn legacy_return_values() {
+	set_min_request_interval(Duration::ZERO);
+	let server = MockServer::start(|request| match request.path.as_str() {
+		"/2022/day/1/input" => Reply::ok("1\n2\n"),
+		"/2022/day/1/answer" => match request.form("answer").as_deref() {
+			Some("3") => Reply::ok(answer_page(CORRECT)),
+			Some("4") => Reply::ok(answer_page(TOO_HIGH)),
+			_ => Reply::ok(answer_page(RATE_LIMITED)),
+		},
+		_ => Reply::status(
+			400,
+			"Puzzle inputs differ by user.  Please log in to get your puzzle input.",
+		),
+	});
+	std::env::set_var(backend::BASE_URL_VAR, server.url());
+	let dir = temp_dir("compat");
+
+	let path = dir.join("1.txt");
+	assert!(get_input_bool("abc123", 2022, 1, &path));
+	assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2");
+	assert!(!get_input_bool("abc123", 2022, 2, dir.join("2.txt")));
+	assert!(!dir.join("2.txt").exists());
+
+	assert_eq!(post_answer_bool("abc123", 2022, 1, 1, 3), Ok(true));
+	assert_eq!(post_answer_bool("abc123", 2022, 1, 1, 4), Ok(false));
+	assert_eq!(post_answer_bool("abc123", 2022, 1, 1, 5), Err(90));
+	assert_eq!(post_answer_bool("abc123", 2022, 2, 1, 3), Ok(false));
+}
@@ -0,0 +1,137 @@
+//! The dashboard's rows are assembled from the cache tree, the run log and your personal times
+
+#![cfg(feature = "tui")]
+
+use aoc_driver::{
+	cache::CacheTree,
+	dashboard::{collect_status, render_text},
+	run_log::{RunOutcome, RunRecord},
+	stats::{DayTimes, PartTime, SelfTimes},
+};
+use chrono::{TimeDelta, Utc};
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+mod common;
+
+use common::temp_dir;
+
+fn write_cache(root: &Path, year: i32, day: i32, json: &str) {
+	let dir = root.join(year.to_string());
+	std::fs::create_dir_all(&dir).unwrap();
+	std::fs::write(dir.join(format!("{day}.json")), json).unwrap();
+}
+
+fn run(day: i32, part: i32, outcome: RunOutcome, minutes_ago: i64) -> RunRecord {
+	let mut record = RunRecord::new(2022, day, part, outcome, "42", Duration::from_millis(42))
+		.with_submit_time(Some(Duration::from_secs(1)));
+	record.timestamp = Utc::now() - TimeDelta::minutes(minutes_ago);
+	record
+}
+
+fn part_time() -> Option<PartTime> {
+	Some(PartTime {
+		time: Some(Duration::from_secs(600)),
+		rank: None,
+		score: None,
+	})
+}
+
+#[test]
+fn status_from_every_source() {
+	let root = temp_dir("dashboard_status");
+	write_cache(
+		&root,
+		2022,
+		1,
+		r#"{"parts":{"1":{"correct_answer":"42","7":{"submission_time":"2022-12-01T05:01:00Z","response":{"Err":"Incorrect"}},"42":{"submission_time":"2022-12-01T05:02:00Z","response":{"Ok":null}}},"2":{"8":{"submission_time":"2022-12-01T05:03:00Z","response":{"Err":"Incorrect"}}}}}"#,
+	);
+	let rate_limited = (Utc::now() - TimeDelta::seconds(10)).to_rfc3339();
+	write_cache(
+		&root,
+		2022,
+		2,
+		&format!(
+			r#"{{"parts":{{"1":{{"5":{{"submission_time":"{rate_limited}","response":{{"Err":{{"RateLimit":"5m"}}}}}}}}}}}}"#
+		),
+	);
+	write_cache(&root, 2022, 3, "not json");
+	let tree = CacheTree::open(&root).unwrap();
+
+	let runs = [
+		run(1, 2, RunOutcome::Incorrect, 5),
+		run(1, 1, RunOutcome::Correct, 10),
+		run(4, 1, RunOutcome::Failed, 1),
+	];
+	let mut self_times = BTreeMap::new();
+	self_times.insert(
+		2022,
+		SelfTimes {
+			days: [
+				(
+					1,
+					DayTimes {
+						part1: part_time(),
+						part2: part_time(),
+					},
+				),
+				(
+					5,
+					DayTimes {
+						part1: part_time(),
+						part2: None,
+					},
+				),
+			]
+			.into(),
+		},
+	);
+
+	let days = collect_status(&tree, &runs, &self_times);
+	let summary = days
+		.iter()
+		.map(|day| (day.day, day.stars, day.attempts))
+		.collect::<Vec<_>>();
+	assert_eq!(summary, [(1, 2, 3), (2, 0, 1), (4, 0, 0), (5, 1, 0)]);
+
+	assert_eq!(days[0].latest_run.as_ref(), Some(&runs[0]));
+	assert_eq!(days[2].latest_run.as_ref(), Some(&runs[2]));
+	assert_eq!(days[0].cooldown, None);
+	let cooldown = days[1].cooldown.unwrap();
+	assert!(cooldown > Duration::from_secs(280) && cooldown <= Duration::from_secs(290));
+
+	// Without personal times, only the cache counts
+	let days = collect_status(&tree, &[], &BTreeMap::new());
+	assert_eq!(days.len(), 2);
+	assert_eq!(days[0].stars, 1);
+}
+
+#[test]
+fn plain_text_table() {
+	let root = temp_dir("dashboard_text");
+	write_cache(
+		&root,
+		2021,
+		7,
+		r#"{"parts":{"1":{"correct_answer":"37","37":{"submission_time":"2021-12-07T05:02:00Z","response":{"Ok":null}}}}}"#,
+	);
+	let tree = CacheTree::open(&root).unwrap();
+	let mut record = run(7, 1, RunOutcome::Correct, 0);
+	record.year = 2021;
+
+	let text = render_text(&collect_status(&tree, &[record], &BTreeMap::new()));
+	assert_eq!(
+		text,
+		"year  day  stars  attempts  cooldown  latest run\n\
+		 2021  7    *      1                   part 1 correct, solved in 42ms, submitted in 1s\n"
+	);
+	assert_eq!(
+		render_text(&[]),
+		"year  day  stars  attempts  cooldown  latest run\n"
+	);
+}
+
+#[test]
+fn missing_cache_root() {
+	let root = temp_dir("dashboard_missing").join("cache");
+	assert!(aoc_driver::dashboard(&root, None).is_err());
+}
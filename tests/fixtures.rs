@@ -0,0 +1,37 @@
+//! Submission responses recorded by `refresh_fixtures`, checked against `classify_submission`
+
+use aoc_driver::{
+	backend::{classify_submission, SubmissionOutcome},
+	error::Error,
+};
+use std::{path::PathBuf, time::Duration};
+
+fn fixture(name: &str) -> String {
+	let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+		.join("tests/fixtures")
+		.join(name);
+	std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+#[test]
+fn incorrect() {
+	let result = classify_submission(&fixture("incorrect.html"));
+	assert!(matches!(result, Err(Error::Incorrect)), "{result:?}");
+}
+
+#[test]
+fn rate_limit() {
+	match classify_submission(&fixture("rate_limit.html")) {
+		Err(Error::RateLimit(limit)) => assert_eq!(limit.wait, Duration::from_secs(56)),
+		result => panic!("{result:?}"),
+	}
+}
+
+#[test]
+fn already_complete() {
+	let result = classify_submission(&fixture("already_complete.html"));
+	assert!(
+		matches!(result, Ok(SubmissionOutcome::AlreadyComplete)),
+		"{result:?}"
+	);
+}
@@ -0,0 +1,69 @@
+//! Both clients decompress gzip responses, and ask for them
+
+#![cfg(feature = "gzip")]
+
+use aoc_driver::*;
+use flate2::{write::GzEncoder, Compression};
+use std::{io::Write, time::Duration};
+
+mod common;
+
+use common::{answer_page, temp_dir, MockServer, Reply, CORRECT};
+
+const INPUT: &str = "3   4\n4   3\n2   5\n";
+
+fn gzip(body: &str) -> Vec<u8> {
+	let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+	encoder.write_all(body.as_bytes()).unwrap();
+	encoder.finish().unwrap()
+}
+
+/// Compresses every response for clients which accept gzip, and refuses the others
+fn server() -> MockServer {
+	MockServer::start(|request| {
+		let accepts_gzip = request
+			.headers
+			.get("accept-encoding")
+			.is_some_and(|encodings| encodings.contains("gzip"));
+		if !accepts_gzip {
+			return Reply::status(406, "gzip only");
+		}
+		let body = match request.path.as_str() {
+			"/2024/day/1/input" => INPUT.to_owned(),
+			"/2024/day/1/answer" => answer_page(CORRECT),
+			_ => return Reply::status(404, "Not Found"),
+		};
+		Reply::ok(gzip(&body)).header("Content-Encoding", "gzip")
+	})
+}
+
+#[test]
+fn ureq_client_decompresses() {
+	set_min_request_interval(Duration::ZERO);
+	let server = server();
+	let dir = temp_dir("gzip_ureq");
+	let client = AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"))
+		.cache_dir(dir.join("cache"));
+
+	assert_eq!(client.get_input(2024, 1).unwrap(), INPUT.trim_end());
+	client.post_answer(2024, 1, 1, 11).unwrap();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn reqwest_client_decompresses() {
+	set_min_request_interval(Duration::ZERO);
+	let server = server();
+	// The async functions only take the base URL from the environment
+	std::env::set_var(backend::BASE_URL_VAR, server.url());
+
+	assert_eq!(
+		get_input_async("abc123", 2024, 1).await.unwrap(),
+		INPUT.trim_end()
+	);
+	post_answer_async("abc123", 2024, 1, 1, None::<&str>, 11)
+		.await
+		.unwrap();
+}
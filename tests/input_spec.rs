@@ -0,0 +1,93 @@
+use aoc_driver::{
+	backend::{AocBackend, SubmissionOutcome},
+	error::{Error, NotSubmittedReason, Result},
+	*,
+};
+use std::cell::Cell;
+
+mod common;
+
+use common::temp_dir;
+
+/// Serves the input "1\n" and accepts every answer, counting the submissions
+#[derive(Default)]
+struct CountingBackend {
+	submissions: Cell<u32>,
+}
+
+impl AocBackend for CountingBackend {
+	fn fetch_input(&self, _year: i32, _day: i32) -> Result<String> { Ok("1\n".to_owned()) }
+
+	fn submit(
+		&self,
+		_year: i32,
+		_day: i32,
+		_part: i32,
+		_answer: &str,
+	) -> Result<SubmissionOutcome> {
+		self.submissions.set(self.submissions.get() + 1);
+		Ok(SubmissionOutcome::Correct)
+	}
+}
+
+fn submit(backend: &CountingBackend, day: i32, input: InputSpec) -> Result<()> {
+	calculate_and_post_with_backend(backend, 2022, day, 1, input, None::<&str>, None, |input| {
+		input.trim().len()
+	})
+}
+
+fn not_submitted(result: Result<()>) -> bool {
+	matches!(
+		result.unwrap_err().inner(),
+		Error::NotSubmitted(NotSubmittedReason::NonCanonicalInput)
+	)
+}
+
+#[test]
+fn literal_input_is_not_submitted() {
+	let backend = CountingBackend::default();
+	let input = InputSpec::Literal("1\n".to_owned());
+	assert!(not_submitted(submit(&backend, 1, input)));
+	assert_eq!(backend.submissions.get(), 0);
+}
+
+#[test]
+fn never_download_file_is_not_submitted() {
+	let backend = CountingBackend::default();
+	let path = temp_dir("never_download").join("2.txt");
+	std::fs::write(&path, "1\n").unwrap();
+	let input = InputSpec::File {
+		path: path.clone(),
+		never_download: false,
+	};
+	assert!(submit(&backend, 2, input).is_ok());
+	let input = InputSpec::File {
+		path,
+		never_download: true,
+	};
+	assert!(not_submitted(submit(&backend, 2, input)));
+	assert_eq!(backend.submissions.get(), 1);
+}
+
+#[test]
+#[cfg(feature = "local_cache")]
+fn run_log_marks_non_canonical_input() {
+	let log_path = temp_dir("non_canonical_log").join("run_log.ndjson");
+	std::env::set_var(run_log::RUN_LOG_VAR, &log_path);
+	let backend = CountingBackend::default();
+	assert!(submit(&backend, 3, InputSpec::Default).is_ok());
+	assert!(not_submitted(submit(
+		&backend,
+		3,
+		InputSpec::Literal("1\n".to_owned())
+	)));
+	std::env::remove_var(run_log::RUN_LOG_VAR);
+
+	let records = run_log::read_run_log(&log_path).unwrap();
+	let marked = records
+		.iter()
+		.filter(|record| record.day == 3)
+		.map(|record| record.non_canonical_input)
+		.collect::<Vec<_>>();
+	assert_eq!(marked, [false, true]);
+}
@@ -0,0 +1,178 @@
+//! The whole fetch, submit, classify and cache pipeline against a mock server
+
+#![cfg(feature = "local_cache")]
+
+use aoc_driver::{
+	error::{Error, Hint},
+	*,
+};
+use std::time::Duration;
+
+mod common;
+
+use common::{answer_page, temp_dir, MockServer, Reply, CORRECT, RATE_LIMITED, TOO_HIGH};
+
+fn puzzle_page(description: &str) -> String {
+	format!("<html><body><main>\n<article class=\"day-desc\"><h2>--- Day 1 ---</h2>{description}</article>\n</main></body></html>\n")
+}
+
+fn server() -> MockServer {
+	MockServer::start(|request| {
+		match (request.method.as_str(), request.path.as_str()) {
+			("GET", "/2020/day/1/input") => Reply::ok("1721\n979\n366\n"),
+			("GET", "/2020/day/1") => Reply::ok(puzzle_page(
+				"<p>For example:</p>\n<pre><code>1721\n979\n366\n</code></pre>\n<p>The answer is <code><em>514579</em></code>.</p>",
+			)),
+			("GET", "/2020/day/2") => Reply::ok(puzzle_page("<p>No example today.</p>")),
+			("POST", "/2020/day/1/answer") => {
+				let text = match request.form("answer").as_deref() {
+					Some("514579") => CORRECT,
+					Some("1") => RATE_LIMITED,
+					_ => TOO_HIGH,
+				};
+				Reply::ok(answer_page(text))
+			}
+			_ => Reply::status(404, "Not Found"),
+		}
+	})
+}
+
+#[test]
+fn fetch_submit_and_cache() {
+	set_min_request_interval(Duration::ZERO);
+	let server = server();
+	let dir = temp_dir("mock_server");
+	let client = AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"))
+		.cache_dir(dir.join("cache"));
+
+	let input = client.get_input(2020, 1).unwrap();
+	assert_eq!(input, "1721\n979\n366");
+	assert_eq!(
+		std::fs::read_to_string(client.input_path(2020, 1)).unwrap(),
+		input
+	);
+	let request = &server.requests()[0];
+	assert_eq!(request.headers["cookie"], "session=abc123");
+
+	// Stored, so not downloaded again
+	client.get_input(2020, 1).unwrap();
+	assert_eq!(server.hits("/2020/day/1/input"), 1);
+
+	assert!(matches!(
+		client.post_answer(2020, 1, 1, 999999),
+		Err(Error::Challenge { source, .. }) if matches!(*source, Error::IncorrectWithHint(Hint::TooHigh))
+	));
+	assert!(matches!(
+		client.post_answer(2020, 1, 1, 1),
+		Err(Error::Challenge { source, .. }) if matches!(*source, Error::RateLimit(_))
+	));
+	client.post_answer(2020, 1, 1, 514579).unwrap();
+	assert_eq!(server.hits("/2020/day/1/answer"), 3);
+
+	let cache = std::fs::read_to_string(client.cache_path(2020, 1)).unwrap();
+	assert!(cache.contains("\"correct_answer\":\"514579\""), "{cache}");
+	assert!(cache.contains("\"999999\""), "{cache}");
+
+	// Answered from the cache without another request
+	client.post_answer(2020, 1, 1, 514579).unwrap();
+	assert!(client.post_answer(2020, 1, 1, 999999).is_err());
+	assert_eq!(server.hits("/2020/day/1/answer"), 3);
+	assert_eq!(server.requests()[1].form("level").as_deref(), Some("1"));
+}
+
+#[test]
+fn stale_incorrect_answers_are_resubmitted_with_cache_ttl() {
+	set_min_request_interval(Duration::ZERO);
+	let server = server();
+	let dir = temp_dir("mock_server_ttl");
+	let client = AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"))
+		.cache_dir(dir.join("cache"));
+	std::fs::create_dir_all(dir.join("cache/2020")).unwrap();
+	std::fs::write(
+		client.cache_path(2020, 1),
+		r#"{"parts":{"1":{"514579":{"submission_time":"2020-12-01T05:01:00Z","response":{"Err":"Incorrect"}}}}}"#,
+	)
+	.unwrap();
+
+	assert!(client
+		.post_answer(2020, 1, 1, 514579)
+		.unwrap_err()
+		.is_incorrect());
+	assert_eq!(server.hits("/2020/day/1/answer"), 0);
+
+	let client = client.cache_ttl(Duration::from_secs(24 * 60 * 60));
+	client.post_answer(2020, 1, 1, 514579).unwrap();
+	assert_eq!(server.hits("/2020/day/1/answer"), 1);
+}
+
+#[test]
+fn invalid_stored_input_is_downloaded_again() {
+	set_min_request_interval(Duration::ZERO);
+	let server = server();
+	let dir = temp_dir("mock_server_invalid_input");
+	let client = AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"));
+	std::fs::create_dir_all(dir.join("inputs/2020")).unwrap();
+
+	for stored in [
+		"",
+		"<!DOCTYPE html>\n<html><body>500 Internal Server Error</body></html>",
+	] {
+		std::fs::write(client.input_path(2020, 1), stored).unwrap();
+		assert_eq!(client.get_input(2020, 1).unwrap(), "1721\n979\n366");
+	}
+	assert_eq!(server.hits("/2020/day/1/input"), 2);
+}
+
+#[test]
+fn client_spaces_out_its_requests() {
+	set_min_request_interval(Duration::ZERO);
+	let server = MockServer::start(|_| Reply::ok("1\n"));
+	let dir = temp_dir("polite_delay");
+	let interval = Duration::from_millis(300);
+	let client = AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"))
+		.cache_dir(dir.join("cache"))
+		.min_request_interval(interval);
+	let clone = client.clone();
+
+	let start = std::time::Instant::now();
+	client.get_input(2020, 1).unwrap();
+	clone.get_input(2020, 2).unwrap();
+	client.get_input(2020, 3).unwrap();
+	assert!(start.elapsed() >= 2 * interval, "{:?}", start.elapsed());
+	assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn example_is_stored_next_to_the_input() {
+	set_min_request_interval(Duration::ZERO);
+	let server = server();
+	let dir = temp_dir("mock_server_example");
+	let client = AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"));
+
+	let example = client.get_example(2020, 1).unwrap();
+	assert_eq!(example.as_deref(), Some("1721\n979\n366"));
+	let path = dir.join("inputs/2020/1.example.txt");
+	assert_eq!(client.example_path(2020, 1), path);
+	assert_eq!(std::fs::read_to_string(&path).unwrap(), "1721\n979\n366");
+
+	// A stored example is never fetched or replaced again
+	std::fs::write(&path, "1\n2\n").unwrap();
+	assert_eq!(
+		client.get_example(2020, 1).unwrap().as_deref(),
+		Some("1\n2\n")
+	);
+	assert_eq!(server.hits("/2020/day/1"), 1);
+
+	assert_eq!(client.get_example(2020, 2).unwrap(), None);
+	assert!(!client.example_path(2020, 2).exists());
+}
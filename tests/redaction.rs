@@ -0,0 +1,44 @@
+//! The session cookie must never show up in formatted clients or sessions
+
+use aoc_driver::{session::Session, *};
+
+mod common;
+
+use common::temp_dir;
+
+const COOKIE: &str = "53616c7465645f5fdeadbeef";
+
+#[test]
+fn session_is_redacted() {
+	let session = Session::new(COOKIE);
+	for formatted in [
+		format!("{session:?}"),
+		format!("{session}"),
+		format!("{session:#?}"),
+	] {
+		assert!(!formatted.contains(COOKIE), "{formatted}");
+		assert!(formatted.contains("redacted"), "{formatted}");
+	}
+	assert_eq!(session.expose(), COOKIE);
+}
+
+#[test]
+fn client_is_redacted() {
+	let client = AocClient::new(COOKIE);
+	for formatted in [format!("{client:?}"), format!("{client:#?}")] {
+		assert!(!formatted.contains(COOKIE), "{formatted}");
+		assert!(formatted.contains("redacted"), "{formatted}");
+	}
+}
+
+#[test]
+fn reloaded_session_is_redacted() {
+	let client = AocClient::new(COOKIE);
+	let path = temp_dir("redaction").join(".session.txt");
+	let reloaded = "c0ffee0123456789";
+	std::fs::write(&path, format!("{reloaded}\n")).unwrap();
+	client.reload_session(&path).unwrap();
+	let formatted = format!("{client:?}");
+	assert!(!formatted.contains(reloaded), "{formatted}");
+	assert!(!formatted.contains(COOKIE), "{formatted}");
+}
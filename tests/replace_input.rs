@@ -0,0 +1,86 @@
+//! Replacing an input through the installed `Storage`, and restoring its backups
+
+use aoc_driver::{
+	error::Result,
+	storage::{set_storage, LocalStorage, Storage},
+	*,
+};
+use std::{
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+
+mod common;
+
+use common::{temp_dir, MockServer, Reply};
+
+/// `LocalStorage`, remembering the paths read and written
+#[derive(Clone, Default)]
+struct RecordingStorage {
+	local: LocalStorage,
+	read: Arc<Mutex<Vec<PathBuf>>>,
+	written: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl Storage for RecordingStorage {
+	fn read(&self, path: &Path) -> Result<Vec<u8>> {
+		self.read.lock().unwrap().push(path.to_owned());
+		self.local.read(path)
+	}
+
+	fn write_atomic(&self, path: &Path, contents: &[u8]) -> Result<()> {
+		self.written.lock().unwrap().push(path.to_owned());
+		self.local.write_atomic(path, contents)
+	}
+
+	fn lock(&self, path: &Path) -> Result<()> { self.local.lock(path) }
+
+	fn unlock(&self, path: &Path) { self.local.unlock(path) }
+
+	fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> { self.local.list(dir) }
+}
+
+#[test]
+fn replacements_and_restore_go_through_storage() {
+	set_min_request_interval(Duration::ZERO);
+	let served = Arc::new(AtomicUsize::new(0));
+	let server = MockServer::start({
+		let served = served.clone();
+		move |_| Reply::ok(format!("{}\n", served.fetch_add(1, Ordering::SeqCst) + 1))
+	});
+	std::env::set_var(backend::BASE_URL_VAR, server.url());
+	let storage = RecordingStorage::default();
+	set_storage(storage.clone());
+
+	let path = temp_dir("replace_input").join("5.txt");
+	std::fs::write(&path, "0").unwrap();
+	let backups = (0..3)
+		.map(|_| {
+			replace_input("abc123", 2022, 5, &path)
+				.unwrap()
+				.backup
+				.unwrap()
+		})
+		.collect::<Vec<_>>();
+
+	assert_eq!(std::fs::read_to_string(&path).unwrap(), "3");
+	for (old, backup) in backups.iter().enumerate() {
+		assert_eq!(std::fs::read_to_string(backup).unwrap(), old.to_string());
+	}
+	let written = storage.written.lock().unwrap().clone();
+	assert_eq!(
+		written.iter().filter(|written| **written == path).count(),
+		3
+	);
+	assert!(backups.iter().all(|backup| written.contains(backup)));
+
+	assert_eq!(restore_input_backup(&path).unwrap(), backups[2]);
+	assert_eq!(std::fs::read_to_string(&path).unwrap(), "2");
+	assert!(storage.read.lock().unwrap().contains(&backups[2]));
+	assert_eq!(storage.written.lock().unwrap().last(), Some(&path));
+	assert!(backups[2].exists());
+}
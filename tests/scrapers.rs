@@ -0,0 +1,115 @@
+//! The HTML scrapers against sanitized copies of real pages
+
+use aoc_driver::{
+	puzzle::{extract_articles, extract_examples, has_part_two, puzzle_to_text, to_markdown},
+	stats::{describe_part, parse_self_times, PartTime},
+};
+use std::{path::PathBuf, time::Duration};
+
+fn fixture(name: &str) -> String {
+	let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+		.join("tests/fixtures")
+		.join(name);
+	std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+#[test]
+fn examples_of_solved_puzzle() {
+	let page = fixture("puzzle_2020_1.html");
+	assert!(has_part_two(&page));
+
+	let examples = extract_examples(&page);
+	assert_eq!(examples.len(), 1);
+	assert_eq!(examples[0].input, "1721\n979\n366\n299\n675\n1456");
+	// Part 2 reuses the example, so both parts' answers follow the one block
+	assert_eq!(examples[0].candidate_answers, ["514579", "241861950"]);
+	assert_eq!(examples[0].answer.as_deref(), Some("241861950"));
+
+	// The descriptions alone give the same examples
+	assert_eq!(extract_examples(&extract_articles(&page)), examples);
+}
+
+#[test]
+fn examples_decode_entities() {
+	let page = fixture("puzzle_2021_10.html");
+	assert!(!has_part_two(&page));
+
+	let examples = extract_examples(&page);
+	assert_eq!(examples.len(), 1);
+	let lines = examples[0].input.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 5);
+	assert_eq!(lines[0], "[({(<(())[]>[[{[]{<()<>>");
+	assert_eq!(lines[4], "[[<[([]))<([[{}[[()]]]");
+	assert_eq!(examples[0].answer.as_deref(), Some("1200"));
+}
+
+#[test]
+fn emphasis_in_code_block_is_dropped() {
+	assert_eq!(
+		to_markdown("<pre><code>12\n<em>34</em></code></pre>"),
+		"```\n12\n34\n```\n"
+	);
+}
+
+#[test]
+fn puzzle_markup_to_markdown() {
+	let page = fixture("puzzle_markup.html");
+	let expected = "\
+## --- Day 7: No Space Left On Device ---
+
+The filesystem consists of a tree of files and directories. Within the terminal output, lines that begin with `$` are *commands you executed*:
+
+- `cd x` moves *in* one level
+- `cd ..` moves *out* one level
+
+Sizes are compared with `a < b` and `a > b`, and output goes to `ls && cat`.
+
+```
+$ cd /
+dir a
+14848514 b.txt
+```
+
+See [the about page](/2022/about) for more.
+";
+	assert_eq!(puzzle_to_text(&page), expected);
+	// The answer outside the descriptions is dropped, as if only they had been converted
+	assert_eq!(to_markdown(&extract_articles(&page)), expected);
+}
+
+#[test]
+fn self_times() {
+	let times = parse_self_times(&fixture("leaderboard_self_2022.html"));
+	assert_eq!(
+		times.days.keys().copied().collect::<Vec<_>>(),
+		[1, 2, 11, 25]
+	);
+
+	let day1 = times.days[&1];
+	assert_eq!(
+		day1.part1,
+		Some(PartTime {
+			time: Some(Duration::from_secs(3 * 60 + 15)),
+			rank: Some(834),
+			score: Some(0),
+		})
+	);
+	assert_eq!(
+		day1.part2.unwrap().time,
+		Some(Duration::from_secs(5 * 60 + 2))
+	);
+
+	let day11 = times.days[&11];
+	assert_eq!(day11.part1.unwrap().time, None);
+	assert_eq!(day11.part2.unwrap().rank, Some(55310));
+
+	let day25 = times.days[&25];
+	assert!(day25.part1.is_some());
+	assert_eq!(day25.part2, None);
+
+	assert_eq!(
+		describe_part(&times, 11, 1).as_deref(),
+		Some("part 1 solved more than 24h after unlock; your rank 62109")
+	);
+	assert_eq!(describe_part(&times, 25, 2), None);
+}
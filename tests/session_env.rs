@@ -0,0 +1,28 @@
+//! `session_from_env` and `Session::from_env` accept and reject the same cookies
+
+use aoc_driver::{error::Error, session::Session, *};
+
+#[test]
+fn env_readers_agree() {
+	std::env::remove_var(PRACTICE_VAR);
+	let read = |value: &str| {
+		std::env::set_var(SESSION_VAR, value);
+		let session = Session::from_env(SESSION_VAR).map(|session| session.expose().to_owned());
+		(session, session_from_env())
+	};
+
+	let (session, plain) = read(" c0ffee\n");
+	assert_eq!(session.unwrap(), "c0ffee");
+	assert_eq!(plain.unwrap(), "c0ffee");
+
+	for (value, missing) in [("", true), (" \n", true), ("not a cookie", false)] {
+		let (session, plain) = read(value);
+		for err in [session.unwrap_err(), plain.unwrap_err()] {
+			match missing {
+				true => assert!(matches!(err, Error::SessionMissing), "{value:?}: {err}"),
+				false => assert!(matches!(err, Error::SessionInvalid), "{value:?}: {err}"),
+			}
+		}
+	}
+	std::env::remove_var(SESSION_VAR);
+}
@@ -0,0 +1,95 @@
+//! Noticing an expired session, and replacing it, against a mock server
+
+use aoc_driver::{error::Error, *};
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+mod common;
+
+use common::{temp_dir, MockServer, Reply};
+
+const LOGGED_OUT: &str = "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n";
+
+/// Accepts the session `c0ffee`. `f1a4e` passes the preflight check, but is refused its input as if logged out
+fn server() -> MockServer {
+	MockServer::start(|request| {
+		let cookie = request.headers.get("cookie").cloned().unwrap_or_default();
+		let session = cookie.trim_start_matches("session=");
+		match (request.path.as_str(), session) {
+			("/events", "c0ffee" | "f1a4e") => Reply::ok(
+				"<header><div class=\"user\">tester <span class=\"star-count\">2*</span></div></header>",
+			),
+			("/events", _) => {
+				Reply::ok("<main><p>To play, please identify yourself via one of these services:</p></main>")
+			}
+			("/2020/day/1/input", "c0ffee") => Reply::ok("1\n"),
+			_ => Reply::status(400, LOGGED_OUT),
+		}
+	})
+}
+
+fn client(server: &MockServer, session: &str, name: &str) -> AocClient {
+	set_min_request_interval(Duration::ZERO);
+	let dir = temp_dir(name);
+	AocClient::new(session)
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"))
+		.cache_dir(dir.join("cache"))
+}
+
+#[test]
+fn expired_session_is_rechecked_reported_once_and_reloaded() {
+	let server = server();
+	let client = client(&server, "bad", "session_expired");
+	let expired = Arc::new(AtomicUsize::new(0));
+	let counter = expired.clone();
+	client.session_monitor().on_expired(move || {
+		counter.fetch_add(1, Ordering::SeqCst);
+	});
+
+	let err = client.get_input(2020, 1).unwrap_err();
+	assert!(matches!(err.inner(), Error::SessionInvalid));
+	// The rejection was confirmed with the preflight check
+	assert_eq!(server.hits("/events"), 1);
+	assert_eq!(expired.load(Ordering::SeqCst), 1);
+	assert!(client.session_monitor().probably_expired());
+
+	// Later requests fail straight away, without reporting again
+	let requests = server.requests().len();
+	assert!(client.get_input(2020, 1).is_err());
+	assert_eq!(server.requests().len(), requests);
+	assert_eq!(expired.load(Ordering::SeqCst), 1);
+
+	// Reloading through a clone fixes every clone
+	let session_file = temp_dir("session_file").join(".session.txt");
+	std::fs::write(&session_file, "c0ffee\n").unwrap();
+	client.clone().reload_session(&session_file).unwrap();
+	assert!(!client.session_monitor().probably_expired());
+	assert_eq!(client.get_input(2020, 1).unwrap(), "1");
+	assert!(client.session_monitor().last_ok().is_some());
+}
+
+#[test]
+fn one_off_rejection_is_not_reported() {
+	let server = server();
+	let client = client(&server, "f1a4e", "session_fluke");
+	let expired = Arc::new(AtomicUsize::new(0));
+	let counter = expired.clone();
+	client.session_monitor().on_expired(move || {
+		counter.fetch_add(1, Ordering::SeqCst);
+	});
+
+	assert!(client.get_input(2020, 1).is_err());
+	assert_eq!(server.hits("/events"), 1);
+	assert_eq!(expired.load(Ordering::SeqCst), 0);
+	assert!(!client.session_monitor().probably_expired());
+
+	// Not given up on, so the next request is still sent
+	assert!(client.get_input(2020, 1).is_err());
+	assert_eq!(server.hits("/2020/day/1/input"), 2);
+}
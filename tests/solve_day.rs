@@ -0,0 +1,109 @@
+//! `day::solve_day` against a mock server
+
+#![cfg(feature = "local_cache")]
+
+use aoc_driver::{
+	day::{solve_day, DaySpec},
+	error::Error,
+	*,
+};
+use std::time::Duration;
+
+mod common;
+
+use common::{answer_page, temp_dir, MockServer, Reply, CORRECT, RATE_LIMITED, TOO_HIGH};
+
+/// Accepts 6 for part 1, and 12 for part 2 unless `rate_limit_part2` is set
+fn server(rate_limit_part2: bool) -> MockServer {
+	MockServer::start(move |request| match request.path.as_str() {
+		"/2021/day/2/input" => Reply::ok("1 2 3\n"),
+		"/2021/day/2/answer" => {
+			let level = request.form("level");
+			let answer = request.form("answer");
+			let text = match (level.as_deref(), answer.as_deref()) {
+				(Some("1"), Some("6")) => CORRECT,
+				(Some("2"), _) if rate_limit_part2 => RATE_LIMITED,
+				(Some("2"), Some("12")) => CORRECT,
+				_ => TOO_HIGH,
+			};
+			Reply::ok(answer_page(text))
+		}
+		_ => Reply::status(404, "Not Found"),
+	})
+}
+
+fn client(server: &MockServer, name: &str) -> AocClient {
+	set_min_request_interval(Duration::ZERO);
+	let dir = temp_dir(name);
+	AocClient::new("abc123")
+		.base_url(server.url())
+		.input_dir(dir.join("inputs"))
+		.cache_dir(dir.join("cache"))
+}
+
+fn sum(input: &str) -> i64 {
+	input
+		.split_whitespace()
+		.map(|n| n.parse::<i64>().unwrap())
+		.sum()
+}
+
+fn spec() -> DaySpec<'static> {
+	DaySpec::parts(sum, |input: &str| sum(input) * 2)
+		.example(1, "4 5", 9)
+		.example(2, "4 5", 18)
+}
+
+#[test]
+fn solves_both_parts() {
+	let server = server(false);
+	let client = client(&server, "solve_day_both");
+
+	let result = solve_day(&client, 2021, 2, &spec()).unwrap();
+	assert_eq!(result.part1.answer, "6");
+	assert_eq!(result.part1.outcome, SubmitOutcome::Correct);
+	let part2 = result.part2.unwrap();
+	assert_eq!(part2.answer, "12");
+	assert_eq!(part2.outcome, SubmitOutcome::Correct);
+	assert_eq!(server.hits("/2021/day/2/input"), 1);
+	assert_eq!(server.hits("/2021/day/2/answer"), 2);
+}
+
+#[test]
+fn rate_limit_on_part2_is_an_outcome() {
+	let server = server(true);
+	let client = client(&server, "solve_day_rate_limit");
+
+	let result = solve_day(&client, 2021, 2, &spec()).unwrap();
+	assert_eq!(result.part1.outcome, SubmitOutcome::Correct);
+	assert_eq!(
+		result.part2.unwrap().outcome,
+		SubmitOutcome::RateLimited(Duration::from_secs(90))
+	);
+}
+
+#[test]
+fn failing_example_makes_no_requests() {
+	let server = server(false);
+	let client = client(&server, "solve_day_example");
+	let spec = DaySpec::part1(sum).example(1, "4 5", 10);
+
+	let err = solve_day(&client, 2021, 2, &spec).unwrap_err();
+	assert!(matches!(
+		err.inner(),
+		Error::ExampleFailed { example: 1, expected, actual } if expected == "10" && actual == "9"
+	));
+	assert!(server.requests().is_empty());
+}
+
+#[test]
+fn part2_skipped_when_part1_wrong() {
+	let server = server(false);
+	let client = client(&server, "solve_day_wrong");
+	let spec = DaySpec::parts(|input: &str| sum(input) + 1, |input: &str| sum(input) * 2);
+
+	let result = solve_day(&client, 2021, 2, &spec).unwrap();
+	assert!(matches!(result.part1.outcome, SubmitOutcome::Incorrect(_)));
+	assert!(result.part2.is_none());
+	assert_eq!(server.hits("/2021/day/2/answer"), 1);
+}
@@ -0,0 +1,254 @@
+//! The cache and inputs behave the same whichever `Storage` they are persisted through
+//!
+//! `set_storage` is global, so the tests in this file take turns
+
+#![cfg(feature = "local_cache")]
+
+use aoc_driver::{
+	backend::{AocBackend, SubmissionOutcome},
+	cache::CacheTree,
+	error::{Error, Hint, RateLimit, Result},
+	storage::{set_storage, supports_atomic_rename, LocalStorage, Storage},
+	*,
+};
+use std::{
+	cell::Cell,
+	collections::{HashMap, HashSet},
+	io::ErrorKind,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, Mutex, MutexGuard, PoisonError,
+	},
+	thread,
+	time::Duration,
+};
+
+mod common;
+
+use common::temp_dir;
+
+static STORAGE_IN_USE: Mutex<()> = Mutex::new(());
+
+fn use_storage(storage: impl Storage + 'static) -> MutexGuard<'static, ()> {
+	let guard = STORAGE_IN_USE
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner);
+	set_storage(storage);
+	guard
+}
+
+/// Files kept in memory, on a pretend filesystem where renaming always fails
+///
+/// Like `LocalStorage` on such a filesystem, writes fall back to writing in place and reading back
+#[derive(Clone, Default)]
+struct RenameHostileStorage {
+	files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
+	locks: Arc<Mutex<HashSet<PathBuf>>>,
+	failed_renames: Arc<AtomicUsize>,
+}
+
+impl RenameHostileStorage {
+	fn files(&self) -> MutexGuard<'_, HashMap<PathBuf, Vec<u8>>> { self.files.lock().unwrap() }
+
+	fn rename(&self, _from: &Path, _to: &Path) -> std::io::Result<()> {
+		self.failed_renames.fetch_add(1, Ordering::SeqCst);
+		Err(std::io::Error::other("rename isn't supported"))
+	}
+}
+
+impl Storage for RenameHostileStorage {
+	fn read(&self, path: &Path) -> Result<Vec<u8>> {
+		match self.files().get(path) {
+			Some(contents) => Ok(contents.clone()),
+			None => Err(std::io::Error::from(ErrorKind::NotFound).into()),
+		}
+	}
+
+	fn write_atomic(&self, path: &Path, contents: &[u8]) -> Result<()> {
+		let mut tmp_path = path.as_os_str().to_owned();
+		tmp_path.push(".tmp");
+		let tmp_path = PathBuf::from(tmp_path);
+		self.files().insert(tmp_path.clone(), contents.to_vec());
+		let renamed = self.rename(&tmp_path, path);
+		self.files().remove(&tmp_path);
+		if renamed.is_err() {
+			self.files().insert(path.to_owned(), contents.to_vec());
+			assert_eq!(self.read(path)?, contents);
+		}
+		Ok(())
+	}
+
+	fn lock(&self, path: &Path) -> Result<()> {
+		while !self.locks.lock().unwrap().insert(path.to_owned()) {
+			thread::sleep(Duration::from_millis(10));
+		}
+		Ok(())
+	}
+
+	fn unlock(&self, path: &Path) { self.locks.lock().unwrap().remove(path); }
+
+	fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+		let mut entries = self
+			.files()
+			.keys()
+			.filter_map(|path| path.strip_prefix(dir).ok()?.components().next())
+			.map(|entry| dir.join(entry))
+			.collect::<Vec<_>>();
+		entries.sort();
+		entries.dedup();
+		match entries.is_empty() {
+			true => Err(std::io::Error::from(ErrorKind::NotFound).into()),
+			false => Ok(entries),
+		}
+	}
+}
+
+/// Serves the input `1\n2\n`, accepting 42 for part 1 and rate limiting every part 2 answer
+#[derive(Default)]
+struct Server {
+	fetches: Cell<u32>,
+	submissions: Cell<u32>,
+}
+
+impl AocBackend for Server {
+	fn fetch_input(&self, _year: i32, _day: i32) -> Result<String> {
+		self.fetches.set(self.fetches.get() + 1);
+		Ok("1\n2\n".to_owned())
+	}
+
+	fn submit(&self, _year: i32, _day: i32, part: i32, answer: &str) -> Result<SubmissionOutcome> {
+		self.submissions.set(self.submissions.get() + 1);
+		match (part, answer.parse::<i64>()) {
+			(1, Ok(42)) => Ok(SubmissionOutcome::Correct),
+			(1, Ok(answer)) if answer > 42 => Err(Error::IncorrectWithHint(Hint::TooHigh)),
+			(1, _) => Err(Error::Incorrect),
+			_ => Err(Error::RateLimit(RateLimit::new(
+				Duration::from_secs(60),
+				chrono::Utc::now(),
+			))),
+		}
+	}
+}
+
+/// Solves and submits through the cache under `root`, checking what reached the server
+fn cache_behaviour(root: &Path) {
+	let (input_path, cache_path) = magic_paths(root, 2022, 1).unwrap();
+	let server = Server::default();
+	let submit = |part, answer: i64, never_download| {
+		let input = InputSpec::File {
+			path: input_path.clone(),
+			never_download,
+		};
+		calculate_and_post_with_backend(
+			&server,
+			2022,
+			1,
+			part,
+			input,
+			Some(&cache_path),
+			None,
+			|input| {
+				assert_eq!(input, "1\n2\n");
+				answer
+			},
+		)
+	};
+
+	assert!(submit(1, 100, false).unwrap_err().is_incorrect());
+	assert_eq!(server.submissions.get(), 1);
+	// Above the known upper bound, and already known to be wrong
+	assert!(submit(1, 150, false).unwrap_err().is_incorrect());
+	assert!(submit(1, 100, false).unwrap_err().is_incorrect());
+	assert_eq!(server.submissions.get(), 1);
+
+	assert!(submit(1, 42, false).is_ok());
+	assert!(submit(1, 42, false).is_ok());
+	assert!(submit(1, 41, false).unwrap_err().is_incorrect());
+	assert_eq!(server.submissions.get(), 2);
+
+	// The stored input is read back rather than downloaded again
+	assert!(submit(1, 42, true).is_ok());
+	assert_eq!(server.fetches.get(), 1);
+
+	let rate_limited =
+		|result: Result<()>| matches!(result.unwrap_err().inner(), Error::RateLimit(_));
+	assert!(rate_limited(submit(2, 5, false)));
+	assert!(rate_limited(submit(2, 5, false)));
+	assert_eq!(server.submissions.get(), 3);
+
+	let days = CacheTree::open(root.join("cache"))
+		.unwrap()
+		.iter()
+		.collect::<Result<Vec<_>>>()
+		.unwrap();
+	assert_eq!(days.len(), 1);
+	assert_eq!((days[0].year, days[0].day), (2022, 1));
+	assert_eq!(days[0].cache.correct_answer(1), Some("42"));
+	assert_eq!(days[0].cache.attempts(1), 2);
+}
+
+#[test]
+fn local_storage() {
+	let _storage = use_storage(LocalStorage::default());
+	cache_behaviour(&temp_dir("storage_local"));
+}
+
+#[test]
+fn local_storage_in_place() {
+	let _storage = use_storage(LocalStorage::in_place());
+	let root = temp_dir("storage_in_place");
+	cache_behaviour(&root);
+	assert!(!root.join("cache/2022/1.json.tmp").exists());
+}
+
+#[test]
+fn rename_hostile_storage() {
+	let storage = RenameHostileStorage::default();
+	let _storage = use_storage(storage.clone());
+	let root = temp_dir("storage_rename_hostile");
+	cache_behaviour(&root);
+
+	assert!(storage.failed_renames.load(Ordering::SeqCst) > 0);
+	assert!(storage
+		.files()
+		.contains_key(&root.join("inputs/2022/1.txt")));
+	// Nothing but the directories reached the disk
+	assert!(!root.join("cache/2022/1.json").exists());
+}
+
+#[test]
+fn rename_probe() {
+	let dir = temp_dir("storage_probe");
+	assert!(supports_atomic_rename(&dir));
+	assert!(LocalStorage::probe(&dir).atomic_rename());
+	assert!(std::fs::read_dir(&dir).unwrap().next().is_none());
+
+	let missing = dir.join("missing");
+	assert!(!supports_atomic_rename(&missing));
+	assert!(!LocalStorage::probe(&missing).atomic_rename());
+	assert!(!LocalStorage::in_place().atomic_rename());
+}
+
+#[test]
+fn in_place_writes() {
+	let storage = LocalStorage::in_place();
+	let path = temp_dir("storage_write_verified").join("1.txt");
+	storage.write_atomic(&path, b"old").unwrap();
+	storage.write_atomic(&path, b"new").unwrap();
+	assert_eq!(storage.read(&path).unwrap(), b"new");
+
+	let failed = storage.write_atomic_with(
+		&path,
+		Box::new(|file| {
+			file.write_all(b"partial")?;
+			Err(Error::InvalidArgument("interrupted".to_owned()))
+		}),
+	);
+	assert!(failed.is_err());
+	assert_eq!(storage.read(&path).unwrap(), b"new");
+	assert_eq!(
+		std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+		1
+	);
+}
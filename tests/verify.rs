@@ -0,0 +1,106 @@
+//! Checking solutions against a manifest of stored inputs and known answers
+
+#![cfg(feature = "local_cache")]
+
+use aoc_driver::{
+	error::Error,
+	verify::{verify_all, VerifyOutcome},
+};
+use std::path::PathBuf;
+
+mod common;
+
+use common::temp_dir;
+
+fn sum(input: &str) -> String {
+	input
+		.lines()
+		.map(|line| line.parse::<i64>().unwrap())
+		.sum::<i64>()
+		.to_string()
+}
+
+fn lines(input: &str) -> String { input.lines().rev().collect::<Vec<_>>().join("\n") }
+
+fn panics(_: &str) -> String { panic!("not solved yet") }
+
+/// A manifest of `entries` (as JSON objects) next to an input `1\n2\n3`
+fn manifest(name: &str, entries: &[&str]) -> PathBuf {
+	let dir = temp_dir(name);
+	std::fs::create_dir(dir.join("inputs")).unwrap();
+	std::fs::write(dir.join("inputs/1.txt"), "1\n2\n3").unwrap();
+	let path = dir.join("manifest.json");
+	std::fs::write(&path, format!("[{}]", entries.join(","))).unwrap();
+	path
+}
+
+fn entry(part: i32, solver: &str, input_file: &str, expected: &str) -> String {
+	format!(
+		r#"{{ "year": 2022, "day": 1, "part": {part}, "solver": "{solver}", "input_file": "{input_file}", "expected": "{expected}" }}"#
+	)
+}
+
+#[test]
+fn every_outcome() {
+	let path = manifest(
+		"verify_outcomes",
+		&[
+			&entry(1, "sum", "inputs/1.txt", "6"),
+			&entry(2, "lines", "inputs/1.txt", "3\\n1\\n2"),
+			&entry(1, "missing", "inputs/1.txt", "6"),
+			&entry(1, "sum", "inputs/2.txt", "6"),
+			&entry(2, "panics", "inputs/1.txt", "6"),
+		],
+	);
+	let report = verify_all(&path, &[("sum", sum), ("lines", lines), ("panics", panics)]).unwrap();
+
+	let outcomes = report
+		.results
+		.iter()
+		.map(|result| &result.outcome)
+		.collect::<Vec<_>>();
+	assert_eq!(outcomes[0], &VerifyOutcome::Passed);
+	assert_eq!(
+		outcomes[1],
+		&VerifyOutcome::WrongAnswer {
+			expected: "3\n1\n2".to_owned(),
+			actual: "3\n2\n1".to_owned(),
+		}
+	);
+	let failure = |outcome: &VerifyOutcome| match outcome {
+		VerifyOutcome::Failed(reason) => reason.clone(),
+		outcome => panic!("{outcome:?} isn't a failure"),
+	};
+	assert_eq!(failure(outcomes[2]), "no solver named \"missing\"");
+	assert!(failure(outcomes[3]).contains("2.txt"));
+	assert_eq!(failure(outcomes[4]), "the solution function panicked");
+	assert!(!report.all_passed());
+	assert_eq!(report.failures().count(), 4);
+
+	let text = report.to_string();
+	assert!(
+		text.contains(
+			"2022 day 1 part 2 (lines): wrong answer\n  -3\n  -1\n  -2\n  +3\n  +2\n  +1\n"
+		),
+		"{text}"
+	);
+	assert!(text.ends_with("1 of 5 passed"), "{text}");
+}
+
+#[test]
+fn all_passed() {
+	let path = manifest("verify_passed", &[&entry(1, "sum", "inputs/1.txt", "6")]);
+	let report = verify_all(&path, &[("sum", sum)]).unwrap();
+	assert!(report.all_passed());
+	assert_eq!(report.to_string(), "1 of 1 passed");
+}
+
+#[test]
+fn invalid_manifest() {
+	let path = manifest("verify_invalid", &["{ \"year\": 2022 }"]);
+	let err = verify_all(&path, &[("sum", sum)]).unwrap_err();
+	assert!(matches!(err, Error::InvalidArgument(_)), "{err}");
+
+	let missing = path.with_file_name("missing.json");
+	assert!(matches!(verify_all(missing, &[]), Err(Error::IO(_))));
+}